@@ -0,0 +1,33 @@
+use nalgebra::Vector2 as V2;
+
+const TOLERANCE: f64 = 0.5;
+
+const MIN_FLAT: f64 = 0.7;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Contacts {
+    pub floor: bool,
+    pub ceiling: bool,
+    pub left_wall: bool,
+    pub right_wall: bool,
+}
+
+impl Contacts {
+    pub fn add(&mut self, normal: V2<f64>, depth: f64, down: f64) {
+        if depth < -TOLERANCE {
+            return;
+        }
+        if normal.y * down >= MIN_FLAT {
+            self.floor = true;
+        } else if normal.y * down <= -MIN_FLAT {
+            self.ceiling = true;
+        } else if normal.x > 0. {
+            self.right_wall = true;
+        } else {
+            self.left_wall = true;
+        }
+    }
+    pub fn touches_wall(&self) -> bool {
+        self.left_wall || self.right_wall
+    }
+}
@@ -0,0 +1,157 @@
+use quicksilver::{load_file, log};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Difficulty {
+    pub size: usize,
+    pub branching: f64,
+    pub dead_ends: f64,
+    pub end_distance: f64,
+    pub jumps: u32,
+    pub hazards: f64,
+}
+
+impl Difficulty {
+    fn interpolate(&self, other: &Self, amount: f64) -> Self {
+        let lerp = |from: f64, to: f64| from + (to - from) * amount;
+        Self {
+            size: lerp(self.size as f64, other.size as f64).round() as usize,
+            branching: lerp(self.branching, other.branching),
+            dead_ends: lerp(self.dead_ends, other.dead_ends),
+            end_distance: lerp(self.end_distance, other.end_distance),
+            jumps: lerp(f64::from(self.jumps), f64::from(other.jumps)).round() as u32,
+            hazards: lerp(self.hazards, other.hazards),
+        }
+    }
+}
+
+pub struct DifficultyProfile {
+    keyframes: Vec<(u32, Difficulty)>,
+}
+
+impl DifficultyProfile {
+    /// Every line is `<level> <size> <branching> <dead ends> <end distance> <jumps> <hazards>`.
+    pub async fn load() -> Self {
+        match load_file("levels/difficulty.txt").await {
+            Ok(loaded) => Self::parse(&String::from_utf8_lossy(&loaded)),
+            Err(_) => Self::default(),
+        }
+    }
+    pub fn parse(text: &str) -> Self {
+        let mut keyframes = Vec::new();
+        for (line_number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_keyframe(line) {
+                Some(keyframe) => keyframes.push(keyframe),
+                None => log::warn!(
+                    "levels/difficulty.txt:{} could not be parsed: {}",
+                    line_number + 1,
+                    line
+                ),
+            }
+        }
+        if keyframes.is_empty() {
+            return Self::default();
+        }
+        keyframes.sort_by_key(|(level, _)| *level);
+        Self { keyframes }
+    }
+    pub fn at(&self, level_id: u32) -> Difficulty {
+        let after = self
+            .keyframes
+            .iter()
+            .position(|(level, _)| *level >= level_id);
+        match after {
+            None => self.keyframes[self.keyframes.len() - 1].1,
+            Some(0) => self.keyframes[0].1,
+            Some(after) => {
+                let (from_level, from) = self.keyframes[after - 1];
+                let (to_level, to) = self.keyframes[after];
+                let amount = f64::from(level_id - from_level) / f64::from(to_level - from_level);
+                from.interpolate(&to, amount)
+            }
+        }
+    }
+}
+
+impl Default for DifficultyProfile {
+    fn default() -> Self {
+        let difficulty = Difficulty {
+            size: 15,
+            branching: 0.,
+            dead_ends: 1.,
+            end_distance: 1.,
+            jumps: 2,
+            hazards: 0.,
+        };
+        Self {
+            keyframes: vec![
+                (1, difficulty),
+                (
+                    50,
+                    Difficulty {
+                        size: 113,
+                        ..difficulty
+                    },
+                ),
+            ],
+        }
+    }
+}
+
+fn parse_keyframe(line: &str) -> Option<(u32, Difficulty)> {
+    let mut parts = line.split_whitespace();
+    let level = parts.next()?.parse().ok()?;
+    let difficulty = Difficulty {
+        size: parts.next()?.parse().ok()?,
+        branching: parts.next()?.parse().ok()?,
+        dead_ends: parts.next()?.parse().ok()?,
+        end_distance: parts.next()?.parse().ok()?,
+        jumps: parts.next()?.parse().ok()?,
+        hazards: parts.next()?.parse().ok()?,
+    };
+    //a maze needs at least two nodes to have somewhere to put the end
+    if parts.next().is_some() || difficulty.size < 2 || difficulty.jumps == 0 {
+        return None;
+    }
+    Some((level, difficulty))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile() -> DifficultyProfile {
+        DifficultyProfile::parse("1 15 0.1 0.5 0.5 2 0\n# comment\n11 35 0.3 1 1 4 0.1\n")
+    }
+
+    #[test]
+    fn first_level_uses_first_keyframe() {
+        assert_eq!(profile().at(1), profile().keyframes[0].1);
+        assert_eq!(profile().at(0), profile().keyframes[0].1);
+    }
+
+    #[test]
+    fn levels_in_between_get_interpolated() {
+        let halfway = profile().at(6);
+        assert_eq!(halfway.size, 25);
+        assert_eq!(halfway.jumps, 3);
+        assert!((halfway.branching - 0.2).abs() < 1e-9);
+        assert!((halfway.hazards - 0.05).abs() < 1e-9);
+    }
+
+    #[test]
+    fn levels_after_the_cap_stay_at_it() {
+        let cap = profile().keyframes[1].1;
+        assert_eq!(profile().at(11), cap);
+        assert_eq!(profile().at(1000), cap);
+    }
+
+    #[test]
+    fn broken_profiles_fall_back_to_default() {
+        let profile = DifficultyProfile::parse("1 1 0 0 0 2 0\nnot a keyframe\n");
+        assert_eq!(profile.at(1), DifficultyProfile::default().at(1));
+    }
+}
@@ -0,0 +1,369 @@
+use crate::input::Action;
+use quicksilver::{
+    lifecycle::{Event, GamepadAxis, GamepadButton, GamepadId},
+    log,
+};
+use std::collections::{HashMap, HashSet};
+
+const DEADZONE: f32 = 0.25;
+const MENU_THRESHOLD: f32 = 0.6;
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PadId {
+    Real(GamepadId),
+    #[cfg(test)]
+    Fake(u32),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PadEvent {
+    Connected(PadId),
+    Disconnected(PadId),
+    Button {
+        pad: PadId,
+        button: GamepadButton,
+        is_down: bool,
+    },
+    Axis {
+        pad: PadId,
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+impl PadEvent {
+    pub fn from_event(event: &Event) -> Option<Self> {
+        Some(match event {
+            Event::GamepadConnected(x) => PadEvent::Connected(PadId::Real(x.gamepad().clone())),
+            Event::GamepadDisconnected(x) => {
+                PadEvent::Disconnected(PadId::Real(x.gamepad().clone()))
+            }
+            Event::GamepadButton(x) => {
+                if x.is_repeat() {
+                    return None;
+                }
+                PadEvent::Button {
+                    pad: PadId::Real(x.gamepad().clone()),
+                    button: x.button(),
+                    is_down: x.is_down(),
+                }
+            }
+            Event::GamepadAxis(x) => PadEvent::Axis {
+                pad: PadId::Real(x.gamepad().clone()),
+                axis: x.axis(),
+                value: x.value(),
+            },
+            _ => return None,
+        })
+    }
+    pub fn action(&self) -> Option<(Action, bool)> {
+        match self {
+            PadEvent::Button {
+                button, is_down, ..
+            } => action_for_button(*button).map(|action| (action, *is_down)),
+            _ => None,
+        }
+    }
+}
+
+pub fn action_for_button(button: GamepadButton) -> Option<Action> {
+    match button {
+        GamepadButton::DPadLeft => Some(Action::MoveLeft),
+        GamepadButton::DPadRight => Some(Action::MoveRight),
+        GamepadButton::South => Some(Action::Jump),
+        GamepadButton::West | GamepadButton::RightShoulder => Some(Action::Flip),
+        GamepadButton::North | GamepadButton::LeftShoulder => Some(Action::Swap),
+        GamepadButton::Select => Some(Action::Hint),
+        //restarting is left out, east is also going back in menus and is easy to press by accident
+        GamepadButton::Start => Some(Action::Pause),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MenuStep {
+    Previous,
+    Next,
+    Confirm,
+    Back,
+}
+
+#[derive(Default)]
+struct PadState {
+    buttons: HashSet<GamepadButton>,
+    axes: HashMap<GamepadAxis, f32>,
+}
+
+#[derive(Default)]
+pub struct Gamepads {
+    pads: HashMap<PadId, PadState>,
+}
+
+impl Gamepads {
+    pub fn handle(&mut self, event: PadEvent) -> Vec<(PadEvent, Option<MenuStep>)> {
+        let mut events = match &event {
+            PadEvent::Disconnected(pad) => self.let_go(pad),
+            _ => Vec::new(),
+        };
+        events.push(event);
+        events
+            .into_iter()
+            .map(|event| {
+                let step = self.track(&event);
+                (event, step)
+            })
+            .collect()
+    }
+    fn let_go(&self, pad: &PadId) -> Vec<PadEvent> {
+        let state = match self.pads.get(pad) {
+            Some(state) => state,
+            None => return Vec::new(),
+        };
+        let buttons = state.buttons.iter().map(|button| PadEvent::Button {
+            pad: pad.clone(),
+            button: *button,
+            is_down: false,
+        });
+        let axes = state
+            .axes
+            .iter()
+            .filter(|(_, value)| **value != 0.)
+            .map(|(axis, _)| PadEvent::Axis {
+                pad: pad.clone(),
+                axis: *axis,
+                value: 0.,
+            });
+        buttons.chain(axes).collect()
+    }
+    fn track(&mut self, event: &PadEvent) -> Option<MenuStep> {
+        match event {
+            PadEvent::Connected(pad) => {
+                log::info!("Gamepad connected: {:?}", pad);
+                self.pads.insert(pad.clone(), PadState::default());
+                None
+            }
+            PadEvent::Disconnected(pad) => {
+                log::info!("Gamepad disconnected: {:?}", pad);
+                self.pads.remove(pad);
+                None
+            }
+            PadEvent::Button {
+                pad,
+                button,
+                is_down,
+            } => {
+                let state = self.pads.entry(pad.clone()).or_default();
+                if *is_down {
+                    state.buttons.insert(*button);
+                } else {
+                    state.buttons.remove(button);
+                }
+                match (button, is_down) {
+                    (GamepadButton::DPadUp, true) | (GamepadButton::DPadLeft, true) => {
+                        Some(MenuStep::Previous)
+                    }
+                    (GamepadButton::DPadDown, true) | (GamepadButton::DPadRight, true) => {
+                        Some(MenuStep::Next)
+                    }
+                    (GamepadButton::South, true) | (GamepadButton::Start, true) => {
+                        Some(MenuStep::Confirm)
+                    }
+                    (GamepadButton::East, true) => Some(MenuStep::Back),
+                    _ => None,
+                }
+            }
+            PadEvent::Axis { pad, axis, value } => {
+                let state = self.pads.entry(pad.clone()).or_default();
+                let before = state.axes.insert(*axis, *value).unwrap_or(0.);
+                //only crossing the threshold moves, so holding the stick doesn't fly through the menu
+                let crossed = |direction: f32| {
+                    before * direction < MENU_THRESHOLD && value * direction >= MENU_THRESHOLD
+                };
+                match axis {
+                    GamepadAxis::LeftStickY | GamepadAxis::LeftStickX if crossed(-1.) => {
+                        Some(MenuStep::Previous)
+                    }
+                    GamepadAxis::LeftStickY | GamepadAxis::LeftStickX if crossed(1.) => {
+                        Some(MenuStep::Next)
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        let value = self
+            .pads
+            .values()
+            .filter_map(|state| state.axes.get(&axis).copied())
+            .fold(0., |strongest: f32, value| {
+                if value.abs() > strongest.abs() {
+                    value
+                } else {
+                    strongest
+                }
+            });
+        if value.abs() < DEADZONE {
+            0.
+        } else {
+            //scaled so just outside the deadzone is still slow, instead of jumping to a quarter of the speed
+            value.signum() * (value.abs() - DEADZONE) / (1. - DEADZONE)
+        }
+    }
+    pub fn is_held(&self, action: Action) -> bool {
+        self.pads.values().any(|state| {
+            state
+                .buttons
+                .iter()
+                .any(|button| action_for_button(*button) == Some(action))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeGamepad {
+        pad: PadId,
+        events: Vec<PadEvent>,
+    }
+
+    impl FakeGamepad {
+        fn new(number: u32) -> Self {
+            Self {
+                pad: PadId::Fake(number),
+                events: Vec::new(),
+            }
+        }
+        fn connect(&mut self) -> &mut Self {
+            self.events.push(PadEvent::Connected(self.pad.clone()));
+            self
+        }
+        fn disconnect(&mut self) -> &mut Self {
+            self.events.push(PadEvent::Disconnected(self.pad.clone()));
+            self
+        }
+        fn button(&mut self, button: GamepadButton, is_down: bool) -> &mut Self {
+            self.events.push(PadEvent::Button {
+                pad: self.pad.clone(),
+                button,
+                is_down,
+            });
+            self
+        }
+        fn axis(&mut self, axis: GamepadAxis, value: f32) -> &mut Self {
+            self.events.push(PadEvent::Axis {
+                pad: self.pad.clone(),
+                axis,
+                value,
+            });
+            self
+        }
+        fn send(&mut self, gamepads: &mut Gamepads) -> Vec<(PadEvent, Option<MenuStep>)> {
+            self.events
+                .drain(..)
+                .flat_map(|event| gamepads.handle(event))
+                .collect()
+        }
+    }
+
+    fn close(value: f32, expected: f32) -> bool {
+        (value - expected).abs() < 1e-6
+    }
+
+    #[test]
+    fn connect() {
+        let mut gamepads = Gamepads::default();
+        let mut pad = FakeGamepad::new(1);
+        let sent = pad
+            .connect()
+            .button(GamepadButton::South, true)
+            .send(&mut gamepads);
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0], (PadEvent::Connected(PadId::Fake(1)), None));
+        assert_eq!(sent[1].1, Some(MenuStep::Confirm));
+        assert!(gamepads.is_held(Action::Jump));
+    }
+
+    #[test]
+    fn disconnect_lets_go_of_everything() {
+        let mut gamepads = Gamepads::default();
+        let mut pad = FakeGamepad::new(1);
+        pad.connect()
+            .button(GamepadButton::South, true)
+            .axis(GamepadAxis::LeftStickX, -1.)
+            .send(&mut gamepads);
+        let sent = pad.disconnect().send(&mut gamepads);
+        let events: Vec<_> = sent.into_iter().map(|(event, _)| event).collect();
+        assert_eq!(
+            events,
+            vec![
+                PadEvent::Button {
+                    pad: PadId::Fake(1),
+                    button: GamepadButton::South,
+                    is_down: false,
+                },
+                PadEvent::Axis {
+                    pad: PadId::Fake(1),
+                    axis: GamepadAxis::LeftStickX,
+                    value: 0.,
+                },
+                PadEvent::Disconnected(PadId::Fake(1)),
+            ]
+        );
+        assert_eq!(events[0].action(), Some((Action::Jump, false)));
+        assert!(!gamepads.is_held(Action::Jump));
+        assert!(close(gamepads.axis(GamepadAxis::LeftStickX), 0.));
+    }
+
+    #[test]
+    fn disconnect_keeps_other_pads() {
+        let mut gamepads = Gamepads::default();
+        let mut first = FakeGamepad::new(1);
+        let mut second = FakeGamepad::new(2);
+        first
+            .connect()
+            .button(GamepadButton::South, true)
+            .send(&mut gamepads);
+        second
+            .connect()
+            .button(GamepadButton::South, true)
+            .send(&mut gamepads);
+        first.disconnect().send(&mut gamepads);
+        assert!(gamepads.is_held(Action::Jump));
+    }
+
+    #[test]
+    fn deadzone() {
+        let mut gamepads = Gamepads::default();
+        let mut pad = FakeGamepad::new(1);
+        pad.connect();
+        let mut pushed_to = |value| {
+            pad.axis(GamepadAxis::LeftStickX, value).send(&mut gamepads);
+            gamepads.axis(GamepadAxis::LeftStickX)
+        };
+        assert!(close(pushed_to(0.2), 0.));
+        assert!(close(pushed_to(-0.2), 0.));
+        assert!(close(pushed_to(0.625), 0.5));
+        assert!(close(pushed_to(-0.625), -0.5));
+        assert!(close(pushed_to(1.), 1.));
+    }
+
+    #[test]
+    fn stick_steps_through_menus_once() {
+        let mut gamepads = Gamepads::default();
+        let mut pad = FakeGamepad::new(1);
+        let steps: Vec<_> = pad
+            .connect()
+            .axis(GamepadAxis::LeftStickY, 0.7)
+            .axis(GamepadAxis::LeftStickY, 0.9)
+            .axis(GamepadAxis::LeftStickY, 0.)
+            .axis(GamepadAxis::LeftStickY, -0.7)
+            .send(&mut gamepads)
+            .into_iter()
+            .filter_map(|(_, step)| step)
+            .collect();
+        assert_eq!(steps, vec![MenuStep::Next, MenuStep::Previous]);
+    }
+}
@@ -0,0 +1,313 @@
+use crate::save::APP_NAME;
+use quicksilver::{
+    lifecycle::Key,
+    log,
+    saving::{load_raw, save_raw, Location},
+};
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Jump,
+    Flip,
+    Swap,
+    Hint,
+    Pause,
+    Restart,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Jump,
+        Action::Flip,
+        Action::Swap,
+        Action::Hint,
+        Action::Pause,
+        Action::Restart,
+    ];
+    fn name(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Jump => "jump",
+            Action::Flip => "flip",
+            Action::Swap => "swap",
+            Action::Hint => "hint",
+            Action::Pause => "pause",
+            Action::Restart => "restart",
+        }
+    }
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Jump => "Jump",
+            Action::Flip => "Flip gravity",
+            Action::Swap => "Swap world",
+            Action::Hint => "Hint",
+            Action::Pause => "Pause",
+            Action::Restart => "Restart",
+        }
+    }
+    fn default_keys(self) -> Vec<Key> {
+        match self {
+            Action::MoveLeft => vec![Key::A, Key::Left],
+            Action::MoveRight => vec![Key::D, Key::Right],
+            Action::Jump => vec![Key::W, Key::Up, Key::Space],
+            Action::Flip => vec![Key::F],
+            Action::Swap => vec![Key::Q],
+            Action::Hint => vec![Key::H],
+            Action::Pause => vec![Key::Escape, Key::P],
+            Action::Restart => vec![Key::R],
+        }
+    }
+}
+
+const BINDABLE_KEYS: [Key; 70] = [
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::Key0,
+    Key::Key1,
+    Key::Key2,
+    Key::Key3,
+    Key::Key4,
+    Key::Key5,
+    Key::Key6,
+    Key::Key7,
+    Key::Key8,
+    Key::Key9,
+    Key::Left,
+    Key::Up,
+    Key::Right,
+    Key::Down,
+    Key::Space,
+    Key::Return,
+    Key::Tab,
+    Key::Back,
+    Key::Escape,
+    Key::LShift,
+    Key::RShift,
+    Key::LControl,
+    Key::RControl,
+    Key::LAlt,
+    Key::RAlt,
+    Key::Comma,
+    Key::Period,
+    Key::Slash,
+    Key::Semicolon,
+    Key::Apostrophe,
+    Key::LBracket,
+    Key::RBracket,
+    Key::Minus,
+    Key::Equals,
+    Key::Numpad0,
+    Key::Numpad1,
+    Key::Numpad2,
+    Key::Numpad3,
+    Key::Numpad4,
+    Key::Numpad5,
+    Key::Numpad6,
+    Key::Numpad7,
+    Key::Numpad8,
+    Key::Numpad9,
+];
+
+pub fn is_bindable(key: Key) -> bool {
+    BINDABLE_KEYS.contains(&key)
+}
+
+pub fn key_name(key: Key) -> String {
+    format!("{:?}", key)
+}
+
+pub fn key_names(bindings: &Bindings, action: Action) -> String {
+    let names: Vec<_> = bindings
+        .keys(action)
+        .iter()
+        .map(|key| key_name(*key))
+        .collect();
+    match names.split_last() {
+        None => String::from("nothing"),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+    }
+}
+
+fn parse_key(name: &str) -> Option<Key> {
+    BINDABLE_KEYS
+        .iter()
+        .copied()
+        .find(|key| key_name(*key) == name)
+}
+
+const BINDINGS_PROFILE: &str = "bindings";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    keys: Vec<(Action, Vec<Key>)>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            keys: Action::ALL
+                .iter()
+                .map(|action| (*action, action.default_keys()))
+                .collect(),
+        }
+    }
+}
+
+impl Bindings {
+    pub fn action_for(&self, key: Key) -> Option<Action> {
+        self.keys
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(action, _)| *action)
+            .or_else(|| {
+                if key == Key::Escape {
+                    Some(Action::Pause)
+                } else {
+                    None
+                }
+            })
+    }
+    pub fn keys(&self, action: Action) -> &[Key] {
+        self.keys
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map(|(_, keys)| keys.as_slice())
+            .unwrap_or(&[])
+    }
+    pub fn is_held(&self, action: Action, held: &HashSet<Key>) -> bool {
+        self.keys(action).iter().any(|key| held.contains(key))
+    }
+    pub fn bind(&mut self, action: Action, key: Key) {
+        for (bound, keys) in &mut self.keys {
+            keys.retain(|bound_key| *bound_key != key);
+            if *bound == action {
+                keys.push(key);
+            }
+        }
+    }
+    pub fn clear(&mut self, action: Action) {
+        for (bound, keys) in &mut self.keys {
+            if *bound == action {
+                keys.clear();
+            }
+        }
+    }
+    pub fn load() -> Self {
+        match load_raw(Location::Config, APP_NAME, BINDINGS_PROFILE) {
+            Ok(saved) => Self::parse(&String::from_utf8_lossy(&saved)),
+            Err(_) => Self::default(),
+        }
+    }
+    pub fn save(&self) {
+        if let Err(err) = save_raw(
+            Location::Config,
+            APP_NAME,
+            BINDINGS_PROFILE,
+            self.to_string().as_bytes(),
+        ) {
+            log::error!("Could not save the bindings: {}", err);
+        }
+    }
+    fn parse(saved: &str) -> Self {
+        let mut bindings = Self::default();
+        //every line is the name of an action followed by the names of its keys
+        for line in saved.lines() {
+            let mut parts = line.split_whitespace();
+            let action = parts
+                .next()
+                .and_then(|name| Action::ALL.iter().find(|action| action.name() == name));
+            let action = match action {
+                Some(action) => *action,
+                None => {
+                    log::warn!("Saved bindings have an unknown action: {}", line);
+                    continue;
+                }
+            };
+            bindings.clear(action);
+            for name in parts {
+                match parse_key(name) {
+                    Some(key) => bindings.bind(action, key),
+                    None => log::warn!("Saved bindings have an unknown key: {}", name),
+                }
+            }
+        }
+        bindings
+    }
+}
+
+impl std::fmt::Display for Bindings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (action, keys) in &self.keys {
+            let keys: Vec<_> = keys.iter().map(|key| key_name(*key)).collect();
+            writeln!(f, "{} {}", action.name(), keys.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut bindings = Bindings::default();
+        bindings.bind(Action::Jump, Key::G);
+        bindings.clear(Action::Hint);
+        bindings.bind(Action::Swap, Key::E);
+        assert_eq!(Bindings::parse(&bindings.to_string()), bindings);
+        assert_eq!(
+            Bindings::parse(&Bindings::default().to_string()),
+            Bindings::default()
+        );
+    }
+
+    #[test]
+    fn rebinding_moves_the_key() {
+        let mut bindings = Bindings::default();
+        bindings.bind(Action::Flip, Key::Space);
+        assert_eq!(bindings.action_for(Key::Space), Some(Action::Flip));
+        assert_eq!(bindings.keys(Action::Flip), &[Key::F, Key::Space]);
+        assert_eq!(bindings.keys(Action::Jump), &[Key::W, Key::Up]);
+    }
+
+    #[test]
+    fn unknown_lines_keep_the_defaults() {
+        let bindings = Bindings::parse("dance X\njump G Nonsense\n");
+        assert_eq!(bindings.keys(Action::Jump), &[Key::G]);
+        assert_eq!(bindings.keys(Action::Flip), &[Key::F]);
+    }
+}
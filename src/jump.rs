@@ -0,0 +1,132 @@
+use crate::tuning::Tuning;
+
+#[derive(Debug, Default)]
+pub struct JumpController {
+    held: bool,
+    buffered: u32,
+    since_floor: Option<u32>,
+    released: bool,
+}
+
+impl JumpController {
+    pub fn press(&mut self, tuning: &Tuning) {
+        if !self.held {
+            self.held = true;
+            self.buffered = tuning.buffer_updates;
+        }
+    }
+    pub fn release(&mut self) {
+        if self.held {
+            self.held = false;
+            self.released = true;
+        }
+    }
+    pub fn reset(&mut self) {
+        self.held = false;
+        self.buffered = 0;
+        self.released = false;
+    }
+    pub fn tick(&mut self, on_floor: bool) {
+        self.buffered = self.buffered.saturating_sub(1);
+        self.since_floor = if on_floor {
+            Some(0)
+        } else {
+            self.since_floor.map(|since| since + 1)
+        };
+    }
+    pub fn wants_jump(&self) -> bool {
+        self.buffered > 0
+    }
+    pub fn can_jump_off_floor(&self, tuning: &Tuning) -> bool {
+        self.since_floor
+            .map(|since| since <= tuning.coyote_updates)
+            .unwrap_or(false)
+    }
+    pub fn missed_floor_jump(&self, tuning: &Tuning) -> bool {
+        self.since_floor
+            .map(|since| since > tuning.coyote_updates)
+            .unwrap_or(false)
+    }
+    pub fn jumped(&mut self) {
+        self.buffered = 0;
+        self.since_floor = None;
+    }
+    pub fn take_release(&mut self) -> bool {
+        std::mem::replace(&mut self.released, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standing() -> JumpController {
+        let mut jump = JumpController::default();
+        jump.tick(true);
+        jump
+    }
+
+    #[test]
+    fn coyote_time_ends_right_after_its_last_update() {
+        let tuning = Tuning::default();
+        let mut jump = standing();
+        for _ in 0..tuning.coyote_updates {
+            jump.tick(false);
+        }
+        assert!(jump.can_jump_off_floor(&tuning));
+        assert!(!jump.missed_floor_jump(&tuning));
+        jump.tick(false);
+        assert!(!jump.can_jump_off_floor(&tuning));
+        assert!(jump.missed_floor_jump(&tuning));
+    }
+
+    #[test]
+    fn jumping_uses_up_coyote_time() {
+        let tuning = Tuning::default();
+        let mut jump = standing();
+        jump.jumped();
+        jump.tick(false);
+        assert!(!jump.can_jump_off_floor(&tuning));
+        assert!(!jump.missed_floor_jump(&tuning));
+    }
+
+    #[test]
+    fn buffered_jump_ends_right_after_its_last_update() {
+        let tuning = Tuning::default();
+        let mut jump = JumpController::default();
+        jump.press(&tuning);
+        for _ in 1..tuning.buffer_updates {
+            jump.tick(false);
+        }
+        assert!(jump.wants_jump());
+        jump.tick(false);
+        assert!(!jump.wants_jump());
+    }
+
+    #[test]
+    fn holding_the_key_buffers_only_once() {
+        let tuning = Tuning::default();
+        let mut jump = JumpController::default();
+        jump.press(&tuning);
+        for _ in 0..tuning.buffer_updates {
+            jump.tick(false);
+            jump.press(&tuning);
+        }
+        assert!(!jump.wants_jump());
+    }
+
+    #[test]
+    fn release_cuts_short_once() {
+        let tuning = Tuning::default();
+        let mut jump = JumpController::default();
+        jump.press(&tuning);
+        jump.release();
+        jump.release();
+        assert!(jump.take_release());
+        assert!(!jump.take_release());
+        jump.press(&tuning);
+        jump.reset();
+        assert!(!jump.take_release());
+        assert!(!jump.wants_jump());
+    }
+}
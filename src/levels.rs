@@ -0,0 +1,749 @@
+use crate::{
+    difficulty::Difficulty,
+    maze_gen::{self, block_to_node, MazeAlgorithm, Placement},
+    movement::{find_start, jump_envelope, JumpEnvelope},
+    seeds, solver,
+    tuning::Tuning,
+    Block, UnknownBlock,
+};
+use quicksilver::{load_file, log};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LevelSource {
+    Authored,
+    Generated,
+    AuthoredOrGenerated,
+}
+
+impl FromStr for LevelSource {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "authored" => Ok(LevelSource::Authored),
+            "generated" => Ok(LevelSource::Generated),
+            "either" => Ok(LevelSource::AuthoredOrGenerated),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LevelConfig {
+    pub source: LevelSource,
+    pub algorithm: MazeAlgorithm,
+    pub start: Placement,
+    pub end: Option<Placement>,
+}
+
+pub struct LevelSources {
+    default: LevelConfig,
+    overrides: HashMap<u32, LevelConfig>,
+}
+
+impl LevelSources {
+    pub fn new(default: LevelConfig) -> Self {
+        Self {
+            default,
+            overrides: HashMap::new(),
+        }
+    }
+    /// Every line is `default <source> [options]` or `<level id> <source> [options]`.
+    pub async fn load() -> Self {
+        let mut sources = Self::default();
+        let loaded = match load_file("levels/sources.txt").await {
+            Ok(loaded) => loaded,
+            Err(_) => return sources,
+        };
+        for (line_number, line) in String::from_utf8_lossy(&loaded).lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let id = parts.next();
+            let parsed = match (id, parse_config(parts, sources.default)) {
+                (Some("default"), Some(config)) => {
+                    sources.default = config;
+                    true
+                }
+                (Some(id), Some(config)) => match id.parse() {
+                    Ok(id) => {
+                        sources.set(id, config);
+                        true
+                    }
+                    Err(_) => false,
+                },
+                _ => false,
+            };
+            if !parsed {
+                log::warn!(
+                    "levels/sources.txt:{} could not be parsed: {}",
+                    line_number + 1,
+                    line
+                );
+            }
+        }
+        sources
+    }
+    pub fn set(&mut self, level_id: u32, config: LevelConfig) {
+        self.overrides.insert(level_id, config);
+    }
+    pub fn get(&self, level_id: u32) -> LevelConfig {
+        self.overrides
+            .get(&level_id)
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+impl Default for LevelSources {
+    fn default() -> Self {
+        Self::new(LevelConfig {
+            source: LevelSource::AuthoredOrGenerated,
+            algorithm: MazeAlgorithm::default(),
+            start: Placement::Random,
+            end: None,
+        })
+    }
+}
+
+fn parse_config<'a>(
+    mut parts: impl Iterator<Item = &'a str>,
+    default: LevelConfig,
+) -> Option<LevelConfig> {
+    let mut config = LevelConfig {
+        source: parts.next()?.parse().ok()?,
+        ..default
+    };
+    for part in parts {
+        match part.splitn(2, '=').collect::<Vec<_>>()[..] {
+            ["start", placement] => config.start = placement.parse().ok()?,
+            ["end", placement] => config.end = Some(placement.parse().ok()?),
+            [algorithm] => config.algorithm = algorithm.parse().ok()?,
+            _ => return None,
+        }
+    }
+    Some(config)
+}
+
+pub(crate) async fn load_level(
+    config: LevelConfig,
+    level_id: u32,
+    seed: u64,
+    difficulty: Difficulty,
+    tuning: &Tuning,
+) -> Result<Level, LevelError> {
+    let path = level_path(level_id);
+    let envelope = jump_envelope(tuning, difficulty.jumps);
+    match config.source {
+        LevelSource::Generated => generate_level(level_id, config, seed, difficulty, envelope),
+        LevelSource::Authored => match load_file(&path).await {
+            Ok(loaded) => parse_authored_level(&path, &loaded, envelope),
+            Err(err) => Err(LevelError::new(
+                path,
+                None,
+                LevelErrorKind::CouldNotLoad(err.to_string()),
+            )),
+        },
+        LevelSource::AuthoredOrGenerated => match load_file(&path).await {
+            Ok(loaded) => parse_authored_level(&path, &loaded, envelope),
+            Err(_) => generate_level(level_id, config, seed, difficulty, envelope),
+        },
+    }
+}
+
+fn parse_authored_level(
+    path: &str,
+    loaded: &[u8],
+    envelope: JumpEnvelope,
+) -> Result<Level, LevelError> {
+    let level = parse_level(path, &String::from_utf8_lossy(loaded))?;
+    let layer = level.layer(level.start_world());
+    if let Err(reason) = solver::solve(layer, envelope) {
+        log::warn!("{} might not be beatable: {}", path, reason);
+    }
+    Ok(level)
+}
+
+fn level_path(level_id: u32) -> String {
+    format!("levels/{}.txt", level_id)
+}
+
+const GENERATE_ATTEMPTS: u64 = 8;
+
+fn generate_level(
+    level_id: u32,
+    config: LevelConfig,
+    seed: u64,
+    difficulty: Difficulty,
+    envelope: JumpEnvelope,
+) -> Result<Level, LevelError> {
+    let file = format!("<generated level {}>", level_id);
+    let mut heaven = generate_heaven(&file, config, seed, difficulty, envelope)?;
+    let hell = generate_hell(&heaven, config, seed, difficulty, envelope);
+    maze_gen::platformer::place_hazards(
+        &mut heaven,
+        difficulty.hazards,
+        envelope,
+        &mut seeds::rng(seeds::derive(seed, seeds::HAZARDS)),
+    );
+    match hell {
+        Some(hell) => Ok(Level { heaven, hell }),
+        None => {
+            log::warn!("No other maze could be made beatable for hell of {}", file);
+            Ok(Level::mirrored(heaven))
+        }
+    }
+}
+
+fn generate_heaven(
+    file: &str,
+    config: LevelConfig,
+    seed: u64,
+    difficulty: Difficulty,
+    envelope: JumpEnvelope,
+) -> Result<Vec<Vec<Block>>, LevelError> {
+    let maze_seed = seeds::derive(seed, seeds::MAZE);
+    let mut level = Vec::new();
+    //the seed of a level never changes, so trying the same one again would fail forever
+    for attempt in 0..GENERATE_ATTEMPTS {
+        let generated = maze_gen::generate_maze_from_seed(
+            &difficulty,
+            config.start,
+            config
+                .end
+                .unwrap_or(Placement::Distance(difficulty.end_distance)),
+            config.algorithm,
+            if attempt == 0 {
+                maze_seed
+            } else {
+                seeds::derive(maze_seed, attempt)
+            },
+        );
+        log::info!(
+            "Generated {} with the start at {:?} and the end at {:?}",
+            file,
+            generated.start,
+            generated.end
+        );
+        level = generated.blocks;
+        validate_level(file, &level)?;
+        if maze_gen::platformer::make_reachable(&mut level, envelope) {
+            return Ok(level);
+        }
+        log::warn!("The end of {} could not be made reachable", file);
+    }
+    //an end placed by hand is kept where it is, even if that means the level can't be played
+    let end_is_fixed = matches!(config.end, Some(Placement::At(_)));
+    if end_is_fixed || !maze_gen::platformer::move_end_within_reach(&mut level, envelope) {
+        return Err(LevelError::new(file, None, LevelErrorKind::Unreachable));
+    }
+    log::warn!("Moved the end of {} to where it can be reached", file);
+    Ok(level)
+}
+
+//hell is a maze of its own between the same start and end, so swapping worlds changes the way through
+fn generate_hell(
+    heaven: &[Vec<Block>],
+    config: LevelConfig,
+    seed: u64,
+    difficulty: Difficulty,
+    envelope: JumpEnvelope,
+) -> Option<Vec<Vec<Block>>> {
+    let start = find_start(heaven)?;
+    let end = heaven.iter().enumerate().find_map(|(y, row)| {
+        row.iter()
+            .position(|block| *block == Block::PlayerEnd)
+            .map(|x| (x, y))
+    })?;
+    let hell_seed = seeds::derive(seed, seeds::HELL);
+    for attempt in 0..GENERATE_ATTEMPTS {
+        let mut hell = maze_gen::generate_maze_from_seed(
+            &difficulty,
+            Placement::At(block_to_node(start)),
+            Placement::At(block_to_node(end)),
+            config.algorithm,
+            seeds::derive(hell_seed, attempt),
+        )
+        .blocks;
+        //the end of heaven might have been moved in between two nodes
+        for block in hell.iter_mut().flatten() {
+            if *block == Block::PlayerEnd {
+                *block = Block::Air;
+            }
+        }
+        hell[end.1][end.0] = Block::PlayerEnd;
+        if maze_gen::platformer::make_reachable(&mut hell, envelope) {
+            maze_gen::platformer::place_hazards(
+                &mut hell,
+                difficulty.hazards,
+                envelope,
+                &mut seeds::rng(seeds::derive(hell_seed, seeds::HAZARDS)),
+            );
+            hell[start.1][start.0] = Block::Air;
+            return Some(hell);
+        }
+    }
+    None
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum World {
+    Heaven,
+    Hell,
+}
+
+impl World {
+    pub const ALL: [World; 2] = [World::Heaven, World::Hell];
+    pub fn other(self) -> Self {
+        match self {
+            World::Heaven => World::Hell,
+            World::Hell => World::Heaven,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Level {
+    pub heaven: Vec<Vec<Block>>,
+    pub hell: Vec<Vec<Block>>,
+}
+
+impl Level {
+    pub fn mirrored(heaven: Vec<Vec<Block>>) -> Self {
+        let hell = heaven
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|block| match block {
+                        Block::PlayerStart => Block::Air,
+                        block => *block,
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { heaven, hell }
+    }
+    pub fn layer(&self, world: World) -> &[Vec<Block>] {
+        match world {
+            World::Heaven => &self.heaven,
+            World::Hell => &self.hell,
+        }
+    }
+    pub fn start_world(&self) -> World {
+        World::ALL
+            .iter()
+            .copied()
+            .find(|world| {
+                self.layer(*world)
+                    .iter()
+                    .any(|row| row.contains(&Block::PlayerStart))
+            })
+            .unwrap_or(World::Heaven)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LevelErrorKind {
+    CouldNotLoad(String),
+    Empty,
+    UnknownBlock(char),
+    RaggedRow { expected: usize, found: usize },
+    MissingStart,
+    MultipleStarts { first: Position },
+    MissingEnd,
+    Unreachable,
+    TooManyWorlds,
+    WorldHeightMismatch { heaven: usize, hell: usize },
+}
+
+impl Display for LevelErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelErrorKind::CouldNotLoad(reason) => write!(f, "could not be loaded: {}", reason),
+            LevelErrorKind::Empty => write!(f, "does not contain any blocks"),
+            LevelErrorKind::UnknownBlock(c) => write!(f, "unknown block {:?}", c),
+            LevelErrorKind::RaggedRow { expected, found } => write!(
+                f,
+                "row is {} blocks wide while the first row is {} blocks wide",
+                found, expected
+            ),
+            LevelErrorKind::MissingStart => write!(f, "has no player start (p)"),
+            LevelErrorKind::MultipleStarts { first } => {
+                write!(f, "second player start, the first is at {}", first)
+            }
+            LevelErrorKind::MissingEnd => write!(f, "has no end (e)"),
+            LevelErrorKind::Unreachable => write!(f, "the end can't be reached from the start"),
+            LevelErrorKind::TooManyWorlds => {
+                write!(f, "only heaven and hell can be separated by ---")
+            }
+            LevelErrorKind::WorldHeightMismatch { heaven, hell } => write!(
+                f,
+                "hell is {} rows high while heaven is {} rows high",
+                hell, heaven
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LevelError {
+    pub file: String,
+    pub position: Option<Position>,
+    pub kind: LevelErrorKind,
+}
+
+impl LevelError {
+    fn new(file: impl Into<String>, position: Option<Position>, kind: LevelErrorKind) -> Self {
+        Self {
+            file: file.into(),
+            position,
+            kind,
+        }
+    }
+}
+
+impl Display for LevelError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some(position) => write!(f, "{}:{}: {}", self.file, position, self.kind),
+            None => write!(f, "{}: {}", self.file, self.kind),
+        }
+    }
+}
+
+impl Error for LevelError {}
+
+/// Rows up to an optional line of dashes (`---`) are heaven, the ones after it are hell.
+pub fn parse_level(file: &str, text: &str) -> Result<Level, LevelError> {
+    let mut layers = vec![Vec::new()];
+    let mut line_of_row = vec![Vec::new()];
+    let mut width = None;
+    let mut separator_line = None;
+    for (line_index, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.is_empty() {
+            continue;
+        }
+        if line.chars().all(|c| c == '-') {
+            if layers.len() == World::ALL.len() {
+                return Err(LevelError::new(
+                    file,
+                    Some(Position {
+                        line: line_index + 1,
+                        column: 1,
+                    }),
+                    LevelErrorKind::TooManyWorlds,
+                ));
+            }
+            layers.push(Vec::new());
+            line_of_row.push(Vec::new());
+            separator_line = Some(line_index + 1);
+            continue;
+        }
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(column, c)| {
+                Block::try_from(c).map_err(|UnknownBlock(c)| {
+                    LevelError::new(
+                        file,
+                        Some(Position {
+                            line: line_index + 1,
+                            column: column + 1,
+                        }),
+                        LevelErrorKind::UnknownBlock(c),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        //both worlds share the same size, so every row of either has to be as wide as the very first
+        let expected = *width.get_or_insert(row.len());
+        if row.len() != expected {
+            return Err(LevelError::new(
+                file,
+                Some(Position {
+                    line: line_index + 1,
+                    column: expected.min(row.len()) + 1,
+                }),
+                LevelErrorKind::RaggedRow {
+                    expected,
+                    found: row.len(),
+                },
+            ));
+        }
+        //there is always at least the layer of heaven to add to
+        layers.last_mut().expect("no layer").push(row);
+        line_of_row
+            .last_mut()
+            .expect("no layer")
+            .push(line_index + 1);
+    }
+    let mut layers = layers.into_iter();
+    let heaven = layers.next().unwrap_or_default();
+    let level = match layers.next() {
+        Some(hell) => {
+            if hell.is_empty() && !heaven.is_empty() {
+                return Err(LevelError::new(
+                    file,
+                    separator_line.map(|line| Position { line, column: 1 }),
+                    LevelErrorKind::Empty,
+                ));
+            }
+            if hell.len() != heaven.len() {
+                return Err(LevelError::new(
+                    file,
+                    None,
+                    LevelErrorKind::WorldHeightMismatch {
+                        heaven: heaven.len(),
+                        hell: hell.len(),
+                    },
+                ));
+            }
+            Level { heaven, hell }
+        }
+        None => {
+            check_start_and_end(file, &[&heaven], |_, y| line_of_row[0][y])?;
+            return Ok(Level::mirrored(heaven));
+        }
+    };
+    check_start_and_end(file, &[&level.heaven, &level.hell], |layer, y| {
+        line_of_row[layer][y]
+    })?;
+    Ok(level)
+}
+
+pub fn validate_level(file: &str, level: &[Vec<Block>]) -> Result<(), LevelError> {
+    check_start_and_end(file, &[level], |_, y| y + 1)
+}
+
+fn check_start_and_end(
+    file: &str,
+    layers: &[&[Vec<Block>]],
+    line_of_row: impl Fn(usize, usize) -> usize,
+) -> Result<(), LevelError> {
+    if layers
+        .iter()
+        .any(|level| level.iter().all(|row| row.is_empty()))
+    {
+        return Err(LevelError::new(file, None, LevelErrorKind::Empty));
+    }
+    let mut start = None;
+    let mut has_end = false;
+    let rows = layers.iter().enumerate().flat_map(|(layer, level)| {
+        level
+            .iter()
+            .enumerate()
+            .map(move |(y, row)| (layer, y, row))
+    });
+    for (layer, y, row) in rows {
+        for (x, block) in row.iter().enumerate() {
+            let position = Position {
+                line: line_of_row(layer, y),
+                column: x + 1,
+            };
+            match block {
+                Block::PlayerStart => match start {
+                    Some(first) => {
+                        return Err(LevelError::new(
+                            file,
+                            Some(position),
+                            LevelErrorKind::MultipleStarts { first },
+                        ))
+                    }
+                    None => start = Some(position),
+                },
+                Block::PlayerEnd => has_end = true,
+                _ => {}
+            }
+        }
+    }
+    if start.is_none() {
+        return Err(LevelError::new(file, None, LevelErrorKind::MissingStart));
+    }
+    if !has_end {
+        return Err(LevelError::new(file, None, LevelErrorKind::MissingEnd));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::DifficultyProfile;
+
+    fn error_of(text: &str) -> (Option<Position>, LevelErrorKind) {
+        let err = parse_level("test.txt", text).expect_err("level should not parse");
+        (err.position, err.kind)
+    }
+
+    fn at(line: usize, column: usize) -> Option<Position> {
+        Some(Position { line, column })
+    }
+
+    #[test]
+    fn parses_mirrored_level() {
+        let level = parse_level("test.txt", "bbbb\nbpeb\nbbbb\n").unwrap();
+        assert_eq!(
+            level.heaven[1],
+            vec![
+                Block::Dirt,
+                Block::PlayerStart,
+                Block::PlayerEnd,
+                Block::Dirt
+            ]
+        );
+        assert_eq!(
+            level.hell[1],
+            vec![Block::Dirt, Block::Air, Block::PlayerEnd, Block::Dirt]
+        );
+        assert_eq!(level.start_world(), World::Heaven);
+    }
+
+    #[test]
+    fn parses_both_worlds() {
+        let level = parse_level("test.txt", "bbb\nbeb\n---\nbbb\nbpb\n").unwrap();
+        assert_eq!(level.heaven[1][1], Block::PlayerEnd);
+        assert_eq!(level.hell[1][1], Block::PlayerStart);
+        assert_eq!(level.start_world(), World::Hell);
+    }
+
+    #[test]
+    fn second_level_needs_both_worlds() {
+        let level = parse_level("2.txt", include_str!("../static/levels/2.txt")).unwrap();
+        assert_eq!(level.start_world(), World::Heaven);
+        assert_ne!(Level::mirrored(level.heaven.clone()), level);
+        let envelope = jump_envelope(&Tuning::default(), 2);
+        assert!(solver::solve(&level.heaven, envelope).is_err());
+    }
+
+    #[test]
+    fn unknown_block() {
+        assert_eq!(
+            error_of("bbbb\nbpxb\nbeeb\n"),
+            (at(2, 3), LevelErrorKind::UnknownBlock('x'))
+        );
+    }
+
+    #[test]
+    fn ragged_row() {
+        assert_eq!(
+            error_of("bbbb\nbpe\nbbbb\n"),
+            (
+                at(2, 4),
+                LevelErrorKind::RaggedRow {
+                    expected: 4,
+                    found: 3
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn empty_lines_keep_their_line_numbers() {
+        assert_eq!(
+            error_of("bbbb\n\nbpeb\nbbpb\n"),
+            (
+                at(4, 3),
+                LevelErrorKind::MultipleStarts {
+                    first: at(3, 2).unwrap()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn missing_start_and_end() {
+        assert_eq!(error_of("bbb\nbeb\n"), (None, LevelErrorKind::MissingStart));
+        assert_eq!(error_of("bbb\nbpb\n"), (None, LevelErrorKind::MissingEnd));
+    }
+
+    #[test]
+    fn empty_level() {
+        assert_eq!(error_of(""), (None, LevelErrorKind::Empty));
+        assert_eq!(error_of("\n\n"), (None, LevelErrorKind::Empty));
+    }
+
+    #[test]
+    fn empty_hell_points_at_the_separator() {
+        assert_eq!(
+            error_of("bbb\nbpb\nbeb\n---\n"),
+            (at(4, 1), LevelErrorKind::Empty)
+        );
+    }
+
+    #[test]
+    fn too_many_worlds() {
+        assert_eq!(
+            error_of("bpe\n---\nbbb\n---\nbbb\n"),
+            (at(4, 1), LevelErrorKind::TooManyWorlds)
+        );
+    }
+
+    #[test]
+    fn world_height_mismatch() {
+        assert_eq!(
+            error_of("bbb\nbpe\n---\nbbb\n"),
+            (
+                None,
+                LevelErrorKind::WorldHeightMismatch { heaven: 2, hell: 1 }
+            )
+        );
+    }
+
+    #[test]
+    fn generated_levels_are_beatable() {
+        let profile = DifficultyProfile::parse(include_str!("../static/levels/difficulty.txt"));
+        let algorithms = [
+            "backtracker",
+            "prim",
+            "kruskal",
+            "eller",
+            "wilson",
+            "binary_tree",
+            "recursive_division",
+        ];
+        for algorithm in algorithms.iter() {
+            for &level_id in &[1, 10, 25] {
+                let config = LevelConfig {
+                    source: LevelSource::Generated,
+                    algorithm: algorithm.parse().unwrap(),
+                    start: Placement::Random,
+                    end: None,
+                };
+                let difficulty = profile.at(level_id);
+                let envelope = jump_envelope(&Tuning::default(), difficulty.jumps);
+                let level = generate_level(level_id, config, 42, difficulty, envelope).unwrap();
+                let start = find_start(&level.heaven).unwrap();
+                for layer in &[&level.heaven, &level.hell] {
+                    assert!(
+                        solver::solve_from(layer, start, envelope).is_ok(),
+                        "{} level {} can't be beaten",
+                        algorithm,
+                        level_id
+                    );
+                }
+                assert_ne!(Level::mirrored(level.heaven.clone()), level);
+            }
+        }
+    }
+}
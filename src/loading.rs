@@ -1,7 +1,7 @@
 use {
-    image::ImageBuffer,
+    image::{ImageBuffer, RgbImage},
     quicksilver::{golem::ColorFormat, graphics::Graphics, graphics::Image as QSImage},
-    rand::seq::SliceRandom,
+    rand::{seq::SliceRandom, RngCore},
 };
 
 pub fn loading_screen(gfx: &Graphics) -> QSImage {
@@ -20,33 +20,48 @@ pub fn loading_screen(gfx: &Graphics) -> QSImage {
             (rgb.blue * 255.) as u8,
         ]);
     }
-    let mut dithered = image::ImageBuffer::new(320, 320);
-    let mut rng = rand::thread_rng();
+    let dithered = dither(&raw, &mut rand::thread_rng());
+    QSImage::from_raw(gfx, Some(&dithered.into_raw()), 320, 320, ColorFormat::RGB).unwrap()
+}
+
+fn quantize(channel: u8) -> u8 {
+    (channel as f32 / (255. / 4.)).round() as u8
+}
+
+pub fn dither(raw: &RgbImage, rng: &mut dyn RngCore) -> RgbImage {
+    let mut dithered = image::ImageBuffer::new(raw.width() * 2, raw.height() * 2);
     for (rx, ry, pixel) in raw.enumerate_pixels() {
-        let (r, g, b) = (pixel[0], pixel[1], pixel[2]);
-        let count = [
-            (r as f32 / (255. / 4.)).round() as u8,
-            (g as f32 / (255. / 4.)).round() as u8,
-            (b as f32 / (255. / 4.)).round() as u8,
-        ];
-        let mut channels = [[0u8; 4], [0u8; 4], [0u8; 4]];
         let mut pixels = [[image::Rgb([0, 0, 0]); 2]; 2];
         for c in 0..3 {
-            for i in 0..count[c] {
-                channels[c][i as usize] = 255u8;
+            let mut channel = [0u8; 4];
+            for value in channel.iter_mut().take(quantize(pixel[c]) as usize) {
+                *value = 255u8;
             }
-            channels[c].shuffle(&mut rng);
-            for x in 0..2 {
-                for y in 0..2 {
-                    pixels[x][y][c] = channels[c][x * 2 + y];
-                }
+            channel.shuffle(rng);
+            for (i, value) in channel.iter().enumerate() {
+                pixels[i / 2][i % 2][c] = *value;
             }
         }
-        for x in 0..2 {
-            for y in 0..2 {
-                dithered.put_pixel(rx * 2 + x, ry * 2 + y, pixels[x as usize][y as usize]);
+        for (x, column) in pixels.iter().enumerate() {
+            for (y, new_pixel) in column.iter().enumerate() {
+                dithered.put_pixel(rx * 2 + x as u32, ry * 2 + y as u32, *new_pixel);
             }
         }
     }
-    QSImage::from_raw(gfx, Some(&dithered.into_raw()), 320, 320, ColorFormat::RGB).unwrap()
+    dithered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seeds;
+
+    #[test]
+    fn same_seed_gives_same_tiles() {
+        let raw =
+            ImageBuffer::from_fn(16, 16, |x, y| image::Rgb([x as u8 * 16, y as u8 * 16, 100]));
+        let tile = |seed| dither(&raw, &mut seeds::rng(seed)).into_raw();
+        assert_eq!(tile(3), tile(3));
+        assert_ne!(tile(3), tile(4));
+    }
 }
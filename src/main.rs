@@ -1,36 +1,85 @@
 //use crate::screens::screen::Screen;
-use mergui::Context;
+use mergui::{Context, MFont};
 use quicksilver::lifecycle::Event::{self, PointerMoved};
 use quicksilver::{
     geom::Vector,
+    golem::ColorFormat,
     graphics::{Color, FontRenderer, Graphics, Image as QSImage},
-    lifecycle::{run, EventStream, Settings, Window},
+    lifecycle::{run, EventStream, Key, Settings, Window},
     load_file,
     mint::Vector2,
     Result,
 };
-mod directions;
+mod difficulty;
+mod gamepad;
 mod screens;
 use async_trait::async_trait;
-use rand::seq::SliceRandom;
+mod contacts;
+mod input;
+mod jump;
+mod levels;
 mod loading;
 mod maze_gen;
+mod movement;
+mod options;
+mod replay;
+mod save;
+mod seeds;
+mod solver;
+mod timer;
+mod timestep;
+mod tuning;
 mod upscaling;
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+};
 
-use crate::upscaling::Loader;
+use crate::{
+    difficulty::DifficultyProfile,
+    gamepad::{Gamepads, MenuStep, PadEvent},
+    input::Bindings,
+    levels::{Level, LevelError, LevelSources},
+    options::Options,
+    replay::Replay,
+    save::{Records, SaveData},
+    screens::stack::ScreenStack,
+    timer::RunTimer,
+    timestep::{FixedTimestep, UPDATES_PER_SECOND},
+    tuning::Tuning,
+    upscaling::Loader,
+};
 
 #[async_trait(?Send)]
 pub(crate) trait Screen {
     async fn draw(&mut self, wrapper: &mut Wrapper<'_>) -> Result<()>;
-    async fn update(&mut self, wrapper: &mut Wrapper<'_>) -> Result<Option<Box<dyn Screen>>>;
+    async fn update(&mut self, wrapper: &mut Wrapper<'_>) -> Result<Option<Transition>>;
     async fn event(
         &mut self,
         _wrapper: &mut Wrapper<'_>,
         _event: &Event,
-    ) -> Result<Option<Box<dyn Screen>>> {
+    ) -> Result<Option<Transition>> {
         Ok(None)
     }
+    async fn pad_event(
+        &mut self,
+        _wrapper: &mut Wrapper<'_>,
+        _event: &PadEvent,
+        _step: Option<MenuStep>,
+    ) -> Result<Option<Transition>> {
+        Ok(None)
+    }
+    fn is_overlay(&self) -> bool {
+        false
+    }
+    fn set_covered(&mut self, _covered: bool) {}
+}
+
+pub(crate) enum Transition {
+    Push(Box<dyn Screen>),
+    Pop,
+    Replace(Box<dyn Screen>),
+    ClearTo(Box<dyn Screen>),
 }
 
 fn main() {
@@ -52,6 +101,9 @@ pub enum Block {
     Air,
     PlayerStart,
     PlayerEnd,
+    Spikes,
+    Flip,
+    Portal,
 }
 
 impl Block {
@@ -63,18 +115,21 @@ impl Block {
     }
 }
 
-impl From<char> for Block {
-    fn from(c: char) -> Self {
-        let c = c
-            .to_lowercase()
-            .next()
-            .expect(&format!("{} was not able to be lowercased", c));
-        match c {
-            'b' => Block::Dirt,
-            'a' => Block::Air,
-            'p' => Block::PlayerStart,
-            'e' => Block::PlayerEnd,
-            x => unreachable!("Got invalid char {}", x),
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UnknownBlock(pub char);
+
+impl TryFrom<char> for Block {
+    type Error = UnknownBlock;
+    fn try_from(c: char) -> std::result::Result<Self, Self::Error> {
+        match c.to_ascii_lowercase() {
+            'b' => Ok(Block::Dirt),
+            'a' => Ok(Block::Air),
+            'p' => Ok(Block::PlayerStart),
+            'e' => Ok(Block::PlayerEnd),
+            's' => Ok(Block::Spikes),
+            'f' => Ok(Block::Flip),
+            'o' => Ok(Block::Portal),
+            _ => Err(UnknownBlock(c)),
         }
     }
 }
@@ -85,6 +140,9 @@ impl From<Block> for &'static str {
             Block::Dirt => "blocks/dirt.png",
             Block::Air | Block::PlayerStart => panic!("has no valid image"),
             Block::PlayerEnd => "blocks/grave.png",
+            Block::Spikes => "blocks/spikes.png",
+            Block::Flip => "blocks/flip.png",
+            Block::Portal => "blocks/portal.png",
         }
     }
 }
@@ -108,13 +166,30 @@ pub(crate) struct Wrapper<'a> {
     pub events: EventStream,
     pub context: Context<'a>,
     pub cursor_at: Vector2<f32>,
-    pub levels: HashMap<u32, Vec<Vec<Block>>>,
-    pub images: HashMap<(u32, u32), QSImage>,
+    pub levels: HashMap<u32, Level>,
+    pub level_sources: LevelSources,
+    pub difficulty: DifficultyProfile,
+    pub tuning: Tuning,
+    pub run_seed: u64,
+    pub level_seed: u64,
+    pub images: HashMap<(Block, u32, u32), QSImage>,
     pub player: PlayerHolder,
     pub raw: HashMap<Block, Vec<u8>>,
     pub end_block: QSImage,
     pub font: FontRenderer,
+    pub small_font: FontRenderer,
+    pub button_font: MFont,
+    pub button_background: QSImage,
     pub scale: Loader,
+    pub reached_level: Option<u32>,
+    pub options: Options,
+    pub records: Records,
+    pub run_timer: RunTimer,
+    pub bindings: Bindings,
+    pub gamepads: Gamepads,
+    pub held_keys: HashSet<Key>,
+    pub should_quit: bool,
+    pub interpolation: f32,
 }
 
 impl<'a> Wrapper<'a> {
@@ -133,63 +208,55 @@ impl<'a> Wrapper<'a> {
             .map(drop)
     }
 
+    pub(crate) fn draw_small_text(&mut self, text: &str, location: Vector) -> Result<()> {
+        self.small_font
+            .draw_wrapping(
+                &mut self.gfx,
+                text,
+                Some(640. - location.x),
+                Color::WHITE,
+                location,
+            )
+            .map(drop)
+    }
+
     pub(crate) async fn get_block(&mut self, block: Block, x: f64, y: f64) -> QSImage {
         let bx = x.floor() as u32 / 32;
         let by = y.floor() as u32 / 32;
         if block == Block::PlayerEnd {
             return self.end_block.clone();
         }
-        if !self.images.contains_key(&(bx, by)) {
-            if !self.raw.contains_key(&block) {
-                self.raw
-                    .insert(block, load_file(String::from(block)).await.unwrap());
-            }
-            let raw = image::load_from_memory(self.raw.get(&block).expect("shouldn't happen"))
-                .unwrap()
-                .into_rgb();
-            let mut dithered = image::ImageBuffer::new(16, 16);
-            let mut rng = rand::thread_rng();
-            for (rx, ry, pixel) in raw.enumerate_pixels() {
-                let (r, g, b) = (pixel[0], pixel[1], pixel[2]);
-                let count = [
-                    (r as f32 / (255. / 4.)).round() as u8,
-                    (g as f32 / (255. / 4.)).round() as u8,
-                    (b as f32 / (255. / 4.)).round() as u8,
-                ];
-                let mut channels = [[0u8; 4], [0u8; 4], [0u8; 4]];
-                let mut pixels = [[image::Rgb([0, 0, 0]); 2]; 2];
-                for c in 0..3 {
-                    for i in 0..count[c] {
-                        channels[c][i as usize] = 255u8;
-                    }
-                    channels[c].shuffle(&mut rng);
-                    for x in 0..2 {
-                        for y in 0..2 {
-                            pixels[x][y][c] = channels[c][x * 2 + y];
-                        }
-                    }
-                }
-                for x in 0..2 {
-                    for y in 0..2 {
-                        dithered.put_pixel(rx * 2 + x, ry * 2 + y, pixels[x as usize][y as usize]);
-                    }
-                }
+        if let Some(image) = self.images.get(&(block, bx, by)) {
+            return image.clone();
+        }
+        let raw = match self.raw.get(&block) {
+            Some(raw) => raw.clone(),
+            None => {
+                let loaded = load_file(String::from(block)).await.unwrap();
+                self.raw.insert(block, loaded.clone());
+                loaded
             }
+        };
+        let raw = image::load_from_memory(&raw).unwrap().into_rgb();
+        let tile_seed = seeds::derive(
+            seeds::derive(self.level_seed, seeds::TILES),
+            u64::from(bx) << 32 | u64::from(by),
+        );
+        let dithered = loading::dither(&raw, &mut seeds::rng(tile_seed));
 
-            let g = self
-                .scale
-                .scale(
-                    dithered.into_raw(),
-                    format!("{}/{}/{}", String::from(block), x, y),
-                    &self.gfx,
-                    (16, 16),
-                    true,
-                )
-                .unwrap();
-            self.images.insert((bx, by), g);
-        }
+        let g = self
+            .scale
+            .scale(
+                dithered.into_raw(),
+                format!("{}/{}", String::from(block), tile_seed),
+                &self.gfx,
+                (16, 16),
+                true,
+            )
+            .unwrap();
+        self.images.insert((block, bx, by), g);
         self.images
-            .get(&(bx, by))
+            .get(&(block, bx, by))
             .expect("shouldn't happen")
             .clone()
 
@@ -204,30 +271,54 @@ impl<'a> Wrapper<'a> {
         //     .unwrap()
         //     .clone()
     }
-    pub(crate) async fn get_level(&mut self, level_id: u32) -> Result<Vec<Vec<Block>>> {
+    pub(crate) fn new_run(&mut self, run_seed: u64) {
+        quicksilver::log::info!("Run seed: {}", run_seed);
+        self.run_seed = run_seed;
+        self.levels = HashMap::new();
+        self.reached_level = None;
+        self.run_timer = RunTimer::default();
+        self.records = Records::default();
+        self.save();
+    }
+    pub(crate) fn save(&self) {
+        SaveData {
+            reached_level: self.reached_level,
+            run_seed: self.run_seed,
+            records: self.records.clone(),
+            options: self.options.clone(),
+        }
+        .save();
+    }
+    pub(crate) async fn get_level(
+        &mut self,
+        level_id: u32,
+    ) -> std::result::Result<Level, LevelError> {
+        self.get_level_of_run(self.run_seed, level_id).await
+    }
+    pub(crate) async fn get_level_of_run(
+        &mut self,
+        run_seed: u64,
+        level_id: u32,
+    ) -> std::result::Result<Level, LevelError> {
         self.images = HashMap::new();
-        if let Some(block) = self.levels.get(&level_id) {
+        //every level of a run gets its own seed, derived from the seed of the run
+        self.level_seed = seeds::derive(run_seed, u64::from(level_id));
+        let is_current_run = run_seed == self.run_seed;
+        if let Some(block) = self.levels.get(&level_id).filter(|_| is_current_run) {
             Ok(block.clone())
         } else {
-            // // println!("got here?");
-            // let loaded = load_file(&format!("levels/{}.txt", level_id)).await?;
-            // // println!("but not here?");
-            // let mut blocks = vec![];
-            // let mut last = Vec::new();
-            // for c in loaded.into_iter().map(|v| char::from(v)) {
-            //     if c == '\n' {
-            //         let mut new = Vec::new();
-            //         std::mem::swap(&mut last, &mut new);
-            //         blocks.push(new);
-            //     } else {
-            //         last.push(Block::from(c))
-            //     }
-            // }
-            // self.levels.insert(level_id, blocks);
-            let size = 13 + 2 * level_id as usize;
-            self.levels
-                .insert(level_id, maze_gen::generate_maze((size, size)));
-            Ok(self.levels.get(&level_id).expect("HOW!?").clone())
+            let level = levels::load_level(
+                self.level_sources.get(level_id),
+                level_id,
+                self.level_seed,
+                self.difficulty.at(level_id),
+                &self.tuning,
+            )
+            .await?;
+            if is_current_run {
+                self.levels.insert(level_id, level.clone());
+            }
+            Ok(level)
         }
     }
 }
@@ -273,8 +364,18 @@ async fn app(window: Window, gfx: Graphics, events: EventStream) -> Result<()> {
         false,
     )?;
 
-    let font = quicksilver::graphics::VectorFont::from_slice(include_bytes!("../static/font.ttf"))
-        .to_renderer(&gfx, 50.)?;
+    let vector_font =
+        quicksilver::graphics::VectorFont::from_slice(include_bytes!("../static/font.ttf"));
+    let font = vector_font.to_renderer(&gfx, 50.)?;
+    let small_font = vector_font.to_renderer(&gfx, 20.)?;
+    let button_font = MFont::from_font(&vector_font, &gfx, 30.)?;
+    let button_background =
+        QSImage::from_raw(&gfx, Some(&[255, 255, 255, 255]), 1, 1, ColorFormat::RGBA)?;
+    let level_sources = LevelSources::load().await;
+    let difficulty = DifficultyProfile::load().await;
+    let tuning = Tuning::load().await;
+    let save = SaveData::load();
+    window.set_fullscreen(save.options.fullscreen);
     let mut wrapper = Wrapper {
         window,
         gfx,
@@ -282,6 +383,12 @@ async fn app(window: Window, gfx: Graphics, events: EventStream) -> Result<()> {
         context,
         cursor_at: Vector2::from_slice(&[0f32, 0f32]),
         levels: HashMap::new(),
+        level_sources,
+        difficulty,
+        tuning,
+        //the run that was played last can be continued, so its levels have to come out the same
+        run_seed: save.run_seed,
+        level_seed: 0,
         images: HashMap::new(),
         raw: HashMap::new(),
         player: PlayerHolder {
@@ -292,24 +399,67 @@ async fn app(window: Window, gfx: Graphics, events: EventStream) -> Result<()> {
         },
         end_block,
         font,
+        small_font,
+        button_font,
+        button_background,
         scale: loader,
+        reached_level: save.reached_level,
+        options: save.options,
+        records: save.records,
+        run_timer: RunTimer::default(),
+        bindings: Bindings::load(),
+        gamepads: Gamepads::default(),
+        held_keys: HashSet::new(),
+        should_quit: false,
+        interpolation: 0.,
     };
-    let mut v: Box<dyn Screen> = Box::new(screens::menu::Menu::new(&mut wrapper, 1).await?);
-    v.draw(&mut wrapper).await?;
+    let mut screens = ScreenStack::new(Box::new(screens::title::Title::new(&mut wrapper)));
+    if let Some(replay) = Replay::from_args() {
+        let watch = screens::game::Game::watch(&mut wrapper, replay).await?;
+        screens.apply(Transition::Push(watch));
+    }
+    screens.draw(&mut wrapper).await?;
+    let mut timestep = FixedTimestep::new(UPDATES_PER_SECOND);
+    #[cfg(debug_assertions)]
+    let mut tuning_reloader = tuning::TuningReloader::new();
     loop {
         while let Some(e) = wrapper.events.next_event().await {
+            if let Some(pad_event) = PadEvent::from_event(&e) {
+                for (pad_event, step) in wrapper.gamepads.handle(pad_event) {
+                    screens.pad_event(&mut wrapper, &pad_event, step).await?;
+                }
+                continue;
+            }
             if let PointerMoved(e) = &e {
                 wrapper.cursor_at = e.location();
             }
             wrapper.context.event(&e, &wrapper.window);
-            if let Some(x) = v.event(&mut wrapper, &e).await? {
-                v = x;
+            match &e {
+                //held down keys keep sending presses, which should only count as the first one
+                Event::KeyboardInput(key) if key.is_down() => {
+                    if !wrapper.held_keys.insert(key.key()) {
+                        continue;
+                    }
+                }
+                Event::KeyboardInput(key) => {
+                    wrapper.held_keys.remove(&key.key());
+                }
+                //keys let go of while the window isn't focused never send an event
+                Event::FocusChanged(focus) if !focus.is_focused() => wrapper.held_keys.clear(),
+                _ => {}
             }
+            screens.event(&mut wrapper, &e).await?;
         }
-        if let Some(x) = v.update(&mut wrapper).await? {
-            v = x;
+        for _ in 0..timestep.advance() {
+            screens.update(&mut wrapper).await?;
+            if wrapper.should_quit || screens.is_empty() {
+                return Ok(());
+            }
         }
-        v.draw(&mut wrapper).await?;
+        wrapper.interpolation = timestep.progress();
+        #[cfg(debug_assertions)]
+        tuning_reloader.reload(&mut wrapper.tuning).await;
+        screens.draw(&mut wrapper).await?;
         wrapper.context.render(&mut wrapper.gfx, &wrapper.window)?;
         wrapper.gfx.present(&wrapper.window)?;
     }
@@ -0,0 +1,30 @@
+use super::{Maze, MazeGenerator};
+use rand::{seq::SliceRandom, RngCore};
+
+pub struct RecursiveBacktracker;
+
+impl MazeGenerator for RecursiveBacktracker {
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let mut visited = vec![vec![false; maze.width()]; maze.height()];
+        let node = maze.random_node(rng);
+        visited[node.1][node.0] = true;
+        let mut position_stack = vec![node];
+        while let Some(&pos) = position_stack.last() {
+            let choices: Vec<_> = maze
+                .neighbours(pos)
+                .into_iter()
+                .filter(|&(x, y)| !visited[y][x])
+                .collect();
+            match choices.choose(rng) {
+                Some(&next) => {
+                    maze.open(pos, next);
+                    visited[next.1][next.0] = true;
+                    position_stack.push(next);
+                }
+                None => {
+                    position_stack.pop();
+                }
+            }
+        }
+    }
+}
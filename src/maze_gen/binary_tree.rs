@@ -0,0 +1,26 @@
+use super::{Maze, MazeGenerator};
+use rand::{Rng, RngCore};
+
+pub struct BinaryTree;
+
+impl MazeGenerator for BinaryTree {
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let width = maze.width();
+        for (x, y) in maze.nodes().collect::<Vec<_>>() {
+            let can_go_up = y > 0;
+            let can_go_right = x + 1 < width;
+            match (can_go_up, can_go_right) {
+                (true, true) => {
+                    if rng.gen_bool(0.5) {
+                        maze.open((x, y), (x, y - 1))
+                    } else {
+                        maze.open((x, y), (x + 1, y))
+                    }
+                }
+                (true, false) => maze.open((x, y), (x, y - 1)),
+                (false, true) => maze.open((x, y), (x + 1, y)),
+                (false, false) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use super::{Maze, MazeGenerator};
+use rand::{seq::SliceRandom, Rng, RngCore};
+
+pub struct Eller;
+
+impl MazeGenerator for Eller {
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let width = maze.width();
+        let height = maze.height();
+        let mut next_set = 0;
+        let mut row_sets: Vec<Option<usize>> = vec![None; width];
+        for y in 0..height {
+            let mut sets: Vec<usize> = row_sets
+                .iter()
+                .map(|set| {
+                    set.unwrap_or_else(|| {
+                        next_set += 1;
+                        next_set - 1
+                    })
+                })
+                .collect();
+            let is_last_row = y + 1 == height;
+            for x in 0..width.saturating_sub(1) {
+                if sets[x] != sets[x + 1] && (is_last_row || rng.gen_bool(0.5)) {
+                    maze.open((x, y), (x + 1, y));
+                    let (old, new) = (sets[x + 1], sets[x]);
+                    for set in sets.iter_mut().filter(|set| **set == old) {
+                        *set = new;
+                    }
+                }
+            }
+            if is_last_row {
+                break;
+            }
+            let mut next_row = vec![None; width];
+            let mut unique_sets = sets.clone();
+            unique_sets.sort();
+            unique_sets.dedup();
+            for set in unique_sets {
+                let mut members: Vec<_> = (0..width).filter(|&x| sets[x] == set).collect();
+                members.shuffle(rng);
+                let drops = rng.gen_range(1, members.len() + 1);
+                for &x in &members[..drops] {
+                    maze.open((x, y), (x, y + 1));
+                    next_row[x] = Some(set);
+                }
+            }
+            row_sets = next_row;
+        }
+    }
+}
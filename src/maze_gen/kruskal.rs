@@ -0,0 +1,37 @@
+use super::{Maze, MazeGenerator, Node};
+use rand::{seq::SliceRandom, RngCore};
+
+pub struct Kruskal;
+
+fn find(parents: &mut Vec<usize>, mut set: usize) -> usize {
+    while parents[set] != set {
+        parents[set] = parents[parents[set]];
+        set = parents[set];
+    }
+    set
+}
+
+impl MazeGenerator for Kruskal {
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let width = maze.width();
+        let index = |(x, y): Node| y * width + x;
+        let mut walls = Vec::new();
+        for node in maze.nodes() {
+            for other in maze.neighbours(node) {
+                if node < other {
+                    walls.push((node, other));
+                }
+            }
+        }
+        walls.shuffle(rng);
+        let mut parents: Vec<_> = (0..maze.width() * maze.height()).collect();
+        for (a, b) in walls {
+            let set_a = find(&mut parents, index(a));
+            let set_b = find(&mut parents, index(b));
+            if set_a != set_b {
+                parents[set_a] = set_b;
+                maze.open(a, b);
+            }
+        }
+    }
+}
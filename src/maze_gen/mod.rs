@@ -0,0 +1,472 @@
+use crate::{difficulty::Difficulty, seeds, Block};
+use rand::{seq::SliceRandom, Rng, RngCore};
+use std::{collections::VecDeque, str::FromStr};
+
+mod backtracker;
+mod binary_tree;
+mod eller;
+mod kruskal;
+pub mod platformer;
+mod prim;
+mod recursive_division;
+mod wilson;
+
+pub use backtracker::RecursiveBacktracker;
+pub use binary_tree::BinaryTree;
+pub use eller::Eller;
+pub use kruskal::Kruskal;
+pub use prim::Prim;
+pub use recursive_division::RecursiveDivision;
+pub use wilson::Wilson;
+
+pub type Node = (usize, usize);
+
+fn node_to_block(value: usize) -> usize {
+    value * 2 + 1
+}
+
+pub fn block_to_node((x, y): (usize, usize)) -> Node {
+    (x.saturating_sub(1) / 2, y.saturating_sub(1) / 2)
+}
+
+enum Wall {
+    Right(usize, usize),
+    Down(usize, usize),
+}
+
+impl Wall {
+    fn between(a: Node, b: Node) -> Option<Self> {
+        let (first, second) = if a <= b { (a, b) } else { (b, a) };
+        if first.1 == second.1 && first.0 + 1 == second.0 {
+            Some(Wall::Right(first.0, first.1))
+        } else if first.0 == second.0 && first.1 + 1 == second.1 {
+            Some(Wall::Down(first.0, first.1))
+        } else {
+            None
+        }
+    }
+}
+
+pub struct Maze {
+    size: (usize, usize),
+    open_right: Vec<Vec<bool>>,
+    open_down: Vec<Vec<bool>>,
+}
+
+impl Maze {
+    pub fn new(size: (usize, usize)) -> Self {
+        Self {
+            size,
+            open_right: vec![vec![false; size.0]; size.1],
+            open_down: vec![vec![false; size.0]; size.1],
+        }
+    }
+    pub fn width(&self) -> usize {
+        self.size.0
+    }
+    pub fn height(&self) -> usize {
+        self.size.1
+    }
+    pub fn nodes(&self) -> impl Iterator<Item = Node> {
+        let (width, height) = self.size;
+        (0..height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+    pub fn random_node(&self, rng: &mut dyn RngCore) -> Node {
+        (rng.gen_range(0, self.size.0), rng.gen_range(0, self.size.1))
+    }
+    pub fn neighbours(&self, (x, y): Node) -> Vec<Node> {
+        let mut neighbours = Vec::with_capacity(4);
+        if y > 0 {
+            neighbours.push((x, y - 1));
+        }
+        if y + 1 < self.size.1 {
+            neighbours.push((x, y + 1));
+        }
+        if x > 0 {
+            neighbours.push((x - 1, y));
+        }
+        if x + 1 < self.size.0 {
+            neighbours.push((x + 1, y));
+        }
+        neighbours
+    }
+    pub fn connected(&self, node: Node) -> Vec<Node> {
+        self.neighbours(node)
+            .into_iter()
+            .filter(|other| self.is_open(node, *other))
+            .collect()
+    }
+    fn wall(&mut self, a: Node, b: Node) -> &mut bool {
+        match Wall::between(a, b) {
+            Some(Wall::Right(x, y)) => &mut self.open_right[y][x],
+            Some(Wall::Down(x, y)) => &mut self.open_down[y][x],
+            None => panic!("{:?} and {:?} are not neighbours", a, b),
+        }
+    }
+    pub fn is_open(&self, a: Node, b: Node) -> bool {
+        match Wall::between(a, b) {
+            Some(Wall::Right(x, y)) => self.open_right[y][x],
+            Some(Wall::Down(x, y)) => self.open_down[y][x],
+            None => false,
+        }
+    }
+    pub fn open(&mut self, a: Node, b: Node) {
+        *self.wall(a, b) = true;
+    }
+    pub fn close(&mut self, a: Node, b: Node) {
+        *self.wall(a, b) = false;
+    }
+    pub fn distances(&self, from: Node) -> Vec<Vec<Option<usize>>> {
+        let mut distances = vec![vec![None; self.size.0]; self.size.1];
+        distances[from.1][from.0] = Some(0);
+        let mut queue = VecDeque::new();
+        queue.push_back((from, 0));
+        while let Some((node, distance)) = queue.pop_front() {
+            for next in self.connected(node) {
+                if distances[next.1][next.0].is_none() {
+                    distances[next.1][next.0] = Some(distance + 1);
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+        distances
+    }
+    pub fn add_loops(&mut self, chance: f64, rng: &mut dyn RngCore) {
+        let nodes: Vec<_> = self.nodes().collect();
+        for node in nodes {
+            for next in self.neighbours(node) {
+                if node < next && !self.is_open(node, next) && rng.gen_bool(chance.max(0.).min(1.))
+                {
+                    self.open(node, next);
+                }
+            }
+        }
+    }
+    pub fn remove_dead_ends(&mut self, keep: f64, rng: &mut dyn RngCore) {
+        let nodes: Vec<_> = self.nodes().collect();
+        for node in nodes {
+            //an earlier dead end might have been opened into this one already
+            if self.connected(node).len() != 1 || rng.gen_bool(keep.max(0.).min(1.)) {
+                continue;
+            }
+            let closed: Vec<_> = self
+                .neighbours(node)
+                .into_iter()
+                .filter(|next| !self.is_open(node, *next))
+                .collect();
+            if let Some(&next) = closed.choose(rng) {
+                self.open(node, next);
+            }
+        }
+    }
+    pub fn dead_ends(&self) -> Vec<Node> {
+        self.nodes()
+            .filter(|&node| self.connected(node).len() == 1)
+            .collect()
+    }
+    pub fn highest_climb_from(&self, from: Node) -> Node {
+        let mut climbs = vec![vec![None; self.size.0]; self.size.1];
+        climbs[from.1][from.0] = Some((0, 0));
+        let mut queue = VecDeque::new();
+        queue.push_back(from);
+        while let Some(node) = queue.pop_front() {
+            let (climb, distance) = climbs[node.1][node.0].expect("only reached nodes are queued");
+            for next in self.connected(node) {
+                if climbs[next.1][next.0].is_none() {
+                    let up = if next.1 < node.1 { 1 } else { 0 };
+                    climbs[next.1][next.0] = Some((climb + up, distance + 1));
+                    queue.push_back(next);
+                }
+            }
+        }
+        self.nodes()
+            .max_by_key(|&(x, y)| climbs[y][x])
+            .unwrap_or(from)
+    }
+    pub fn at_distance_from(&self, from: Node, share: f64, rng: &mut dyn RngCore) -> Node {
+        let distances = self.distances(from);
+        let farthest = self
+            .nodes()
+            .filter_map(|(x, y)| distances[y][x])
+            .max()
+            .unwrap_or(0);
+        let target = (farthest as f64 * share.max(0.).min(1.)).round() as usize;
+        let off_by = |(x, y): Node| {
+            distances[y][x]
+                .map(|distance| (distance as isize - target as isize).abs())
+                .unwrap_or(isize::MAX)
+        };
+        let closest = self
+            .nodes()
+            .filter(|&node| node != from)
+            .map(off_by)
+            .min()
+            .unwrap_or(0);
+        let candidates: Vec<_> = self
+            .nodes()
+            .filter(|&node| node != from && off_by(node) == closest)
+            .collect();
+        candidates.choose(rng).copied().unwrap_or(from)
+    }
+    pub fn to_blocks(&self) -> Vec<Vec<Block>> {
+        let mut blocks =
+            vec![vec![Block::Dirt; node_to_block(self.size.0)]; node_to_block(self.size.1)];
+        for (x, y) in self.nodes() {
+            blocks[node_to_block(y)][node_to_block(x)] = Block::Air;
+            if self.open_right[y][x] {
+                blocks[node_to_block(y)][node_to_block(x) + 1] = Block::Air;
+            }
+            if self.open_down[y][x] {
+                blocks[node_to_block(y) + 1][node_to_block(x)] = Block::Air;
+            }
+        }
+        blocks
+    }
+}
+
+pub trait MazeGenerator {
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Placement {
+    Random,
+    Distance(f64),
+    Climb,
+    DeadEnd,
+    At(Node),
+}
+
+impl Placement {
+    fn is_relative(self) -> bool {
+        match self {
+            Placement::Distance(_) | Placement::Climb => true,
+            Placement::Random | Placement::DeadEnd | Placement::At(_) => false,
+        }
+    }
+}
+
+impl FromStr for Placement {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Placement::Random),
+            "farthest" => Ok(Placement::Distance(1.)),
+            "climb" => Ok(Placement::Climb),
+            "dead_end" => Ok(Placement::DeadEnd),
+            _ => {
+                let mut coordinates = s.splitn(2, ',').map(str::parse);
+                match (coordinates.next(), coordinates.next()) {
+                    (Some(Ok(x)), Some(Ok(y))) => Ok(Placement::At((x, y))),
+                    _ => Err(()),
+                }
+            }
+        }
+    }
+}
+
+impl Maze {
+    pub fn place(&self, placement: Placement, other: Option<Node>, rng: &mut dyn RngCore) -> Node {
+        let other_or_random = other.unwrap_or_else(|| self.random_node(rng));
+        let random_except_other = |rng: &mut dyn RngCore| {
+            let nodes: Vec<_> = self.nodes().filter(|node| Some(*node) != other).collect();
+            nodes.choose(rng).copied().unwrap_or(other_or_random)
+        };
+        let node = match placement {
+            Placement::Random => random_except_other(rng),
+            Placement::Distance(share) => self.at_distance_from(other_or_random, share, rng),
+            Placement::Climb => self.highest_climb_from(other_or_random),
+            Placement::DeadEnd => {
+                let dead_ends: Vec<_> = self
+                    .dead_ends()
+                    .into_iter()
+                    .filter(|node| Some(*node) != other)
+                    .collect();
+                match dead_ends.choose(rng) {
+                    Some(dead_end) => *dead_end,
+                    None => random_except_other(rng),
+                }
+            }
+            Placement::At((x, y)) => (x.min(self.size.0 - 1), y.min(self.size.1 - 1)),
+        };
+        if Some(node) == other {
+            self.at_distance_from(node, 0., rng)
+        } else {
+            node
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MazeAlgorithm {
+    RecursiveBacktracker,
+    Prim,
+    Kruskal,
+    Eller,
+    Wilson,
+    BinaryTree,
+    RecursiveDivision,
+}
+
+impl MazeAlgorithm {
+    pub fn generator(self) -> &'static dyn MazeGenerator {
+        match self {
+            MazeAlgorithm::RecursiveBacktracker => &RecursiveBacktracker,
+            MazeAlgorithm::Prim => &Prim,
+            MazeAlgorithm::Kruskal => &Kruskal,
+            MazeAlgorithm::Eller => &Eller,
+            MazeAlgorithm::Wilson => &Wilson,
+            MazeAlgorithm::BinaryTree => &BinaryTree,
+            MazeAlgorithm::RecursiveDivision => &RecursiveDivision,
+        }
+    }
+}
+
+impl Default for MazeAlgorithm {
+    fn default() -> Self {
+        MazeAlgorithm::RecursiveBacktracker
+    }
+}
+
+impl FromStr for MazeAlgorithm {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "backtracker" => Ok(MazeAlgorithm::RecursiveBacktracker),
+            "prim" => Ok(MazeAlgorithm::Prim),
+            "kruskal" => Ok(MazeAlgorithm::Kruskal),
+            "eller" => Ok(MazeAlgorithm::Eller),
+            "wilson" => Ok(MazeAlgorithm::Wilson),
+            "binary_tree" => Ok(MazeAlgorithm::BinaryTree),
+            "recursive_division" => Ok(MazeAlgorithm::RecursiveDivision),
+            _ => Err(()),
+        }
+    }
+}
+
+pub struct GeneratedMaze {
+    pub blocks: Vec<Vec<Block>>,
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+}
+
+pub fn generate_maze_from_seed(
+    difficulty: &Difficulty,
+    start: Placement,
+    end: Placement,
+    algorithm: MazeAlgorithm,
+    seed: u64,
+) -> GeneratedMaze {
+    generate_maze(
+        difficulty,
+        start,
+        end,
+        algorithm.generator(),
+        &mut seeds::rng(seed),
+    )
+}
+
+/// resulting size is (size * 2 + 1, size * 2 + 1)
+pub fn generate_maze(
+    difficulty: &Difficulty,
+    start: Placement,
+    end: Placement,
+    generator: &dyn MazeGenerator,
+    rng: &mut dyn RngCore,
+) -> GeneratedMaze {
+    let mut maze = Maze::new((difficulty.size, difficulty.size));
+    generator.carve(&mut maze, rng);
+    maze.add_loops(difficulty.branching, rng);
+    maze.remove_dead_ends(difficulty.dead_ends, rng);
+    //if only the start depends on the end, the end has to be placed first
+    let (start, end) = if start.is_relative() && !end.is_relative() {
+        let end = maze.place(end, None, rng);
+        (maze.place(start, Some(end), rng), end)
+    } else {
+        let start = maze.place(start, None, rng);
+        (start, maze.place(end, Some(start), rng))
+    };
+    let start = (node_to_block(start.0), node_to_block(start.1));
+    let end = (node_to_block(end.0), node_to_block(end.1));
+    let mut blocks = maze.to_blocks();
+    blocks[start.1][start.0] = Block::PlayerStart;
+    blocks[end.1][end.0] = Block::PlayerEnd;
+    GeneratedMaze { blocks, start, end }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::validate_level;
+
+    const ALGORITHMS: [MazeAlgorithm; 7] = [
+        MazeAlgorithm::RecursiveBacktracker,
+        MazeAlgorithm::Prim,
+        MazeAlgorithm::Kruskal,
+        MazeAlgorithm::Eller,
+        MazeAlgorithm::Wilson,
+        MazeAlgorithm::BinaryTree,
+        MazeAlgorithm::RecursiveDivision,
+    ];
+
+    fn difficulty() -> Difficulty {
+        Difficulty {
+            size: 5,
+            branching: 0.,
+            dead_ends: 1.,
+            end_distance: 1.,
+            jumps: 2,
+            hazards: 0.,
+        }
+    }
+
+    #[test]
+    fn colliding_placements_get_moved() {
+        let mut rng = seeds::rng(1);
+        let mut maze = Maze::new((5, 5));
+        RecursiveBacktracker.carve(&mut maze, &mut rng);
+        let start = maze.place(Placement::At((2, 2)), None, &mut rng);
+        for placement in &[Placement::Distance(0.), Placement::At((2, 2))] {
+            let end = maze.place(*placement, Some(start), &mut rng);
+            assert_ne!(end, start);
+            assert!(maze.is_open(start, end));
+        }
+    }
+
+    #[test]
+    fn start_and_end_never_share_a_block() {
+        for (start, end) in &[
+            (Placement::At((0, 0)), Placement::At((0, 0))),
+            (Placement::Random, Placement::Distance(0.)),
+            (Placement::Distance(0.), Placement::At((4, 4))),
+        ] {
+            let generated = generate_maze(
+                &difficulty(),
+                *start,
+                *end,
+                &RecursiveBacktracker,
+                &mut seeds::rng(2),
+            );
+            assert_ne!(generated.start, generated.end);
+            assert_eq!(validate_level("test", &generated.blocks), Ok(()));
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_maze() {
+        for algorithm in ALGORITHMS.iter() {
+            let maze = |seed| {
+                generate_maze_from_seed(
+                    &difficulty(),
+                    Placement::Random,
+                    Placement::Distance(1.),
+                    *algorithm,
+                    seed,
+                )
+            };
+            let (first, again) = (maze(5), maze(5));
+            assert_eq!(first.blocks, again.blocks);
+            assert_eq!((first.start, first.end), (again.start, again.end));
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{
+    movement::{explore, find_start, is_free, moves, touches_end, Cell, Exploration, JumpEnvelope},
+    solver, Block,
+};
+use rand::{Rng, RngCore};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+fn route_ignoring_physics(level: &[Vec<Block>]) -> Option<Vec<Cell>> {
+    let start = find_start(level)?;
+    let mut came_from = HashMap::new();
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+    while let Some(cell) = queue.pop_front() {
+        if touches_end(level, cell) {
+            let mut route = vec![cell];
+            let mut current = cell;
+            while let Some(&previous) = came_from.get(&current) {
+                route.push(previous);
+                current = previous;
+            }
+            route.reverse();
+            return Some(route);
+        }
+        let (x, y) = cell;
+        let mut next = vec![(x + 1, y), (x, y + 1)];
+        if x > 0 {
+            next.push((x - 1, y));
+        }
+        if y > 0 {
+            next.push((x, y - 1));
+        }
+        for next in next {
+            if next != start && is_free(level, next) && !came_from.contains_key(&next) {
+                came_from.insert(next, cell);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+fn footholds(level: &[Vec<Block>], (x, y): Cell) -> Vec<Cell> {
+    let height = level.len();
+    let width = level.first().map(Vec::len).unwrap_or(0);
+    let mut sides = vec![(x + 1, y)];
+    if x > 0 {
+        sides.push((x - 1, y));
+    }
+    sides
+        .into_iter()
+        .filter(|&(side_x, side_y)| {
+            //never break the outer wall, the player would fall out of the level
+            side_x > 0
+                && side_x + 1 < width
+                && side_y > 0
+                && side_y + 1 < height
+                && level[side_y][side_x] == Block::Dirt
+                && level[side_y + 1][side_x].is_colideable()
+        })
+        .collect()
+}
+
+fn carve_along_route(level: &mut Vec<Vec<Block>>, route: &[Cell], envelope: JumpEnvelope) {
+    let mut index = 0;
+    while index < route.len() {
+        let (x, bottom) = route[index];
+        let climb_length = route[index + 1..]
+            .iter()
+            .zip(route[index..].iter())
+            .take_while(|(next, previous)| next.0 == x && next.1 + 1 == previous.1)
+            .count();
+        if climb_length <= envelope.max_climb {
+            index += climb_length.max(1);
+            continue;
+        }
+        let top = bottom - climb_length;
+        let mut standing_at = bottom;
+        while standing_at > top + envelope.max_climb {
+            let foothold = (standing_at - envelope.max_climb..standing_at)
+                .filter_map(|y| footholds(level, (x, y)).first().copied())
+                .next();
+            match foothold {
+                Some((foothold_x, foothold_y)) => {
+                    level[foothold_y][foothold_x] = Block::Air;
+                    standing_at = foothold_y;
+                }
+                None => break,
+            }
+        }
+        index += climb_length;
+    }
+}
+
+fn ledges(level: &[Vec<Block>], (x, y): Cell, route: &[Cell]) -> Vec<(Cell, Cell)> {
+    let height = level.len();
+    let width = level.first().map(Vec::len).unwrap_or(0);
+    let mut sides = vec![(x + 1, y)];
+    if x > 0 {
+        sides.push((x - 1, y));
+    }
+    sides
+        .into_iter()
+        .filter(|&(side_x, side_y)| {
+            side_x > 0
+                && side_x + 1 < width
+                && side_y > 0
+                && side_y + 2 < height
+                && level[side_y][side_x] == Block::Dirt
+                && level[side_y + 1][side_x] == Block::Air
+                && !route.contains(&(side_x, side_y + 1))
+        })
+        .map(|(side_x, side_y)| ((side_x, side_y), (side_x, side_y + 1)))
+        .collect()
+}
+
+fn opens_up_nearby(
+    level: &[Vec<Block>],
+    exploration: &Exploration,
+    (x, y): Cell,
+    envelope: JumpEnvelope,
+) -> bool {
+    let was_floor = y > 0 && exploration.standing.contains_key(&(x, y - 1));
+    let reach_x = envelope.max_drift + 1;
+    let reach_y = envelope.max_climb + 1;
+    exploration
+        .standing
+        .keys()
+        .filter(|&&(standing_x, standing_y)| {
+            standing_x + reach_x >= x
+                && standing_x <= x + reach_x
+                && standing_y + reach_y >= y
+                && standing_y <= y + reach_y
+        })
+        .flat_map(|&standing| moves(level, standing, envelope))
+        .any(|possible_move| {
+            possible_move
+                .to()
+                .filter(|&to| !(was_floor && to == (x, y)))
+                .map(|to| !exploration.standing.contains_key(&to))
+                .unwrap_or(false)
+                || possible_move
+                    .path
+                    .iter()
+                    .any(|&cell| touches_end(level, cell))
+        })
+}
+
+const MAX_EXPLORATIONS: usize = 32;
+
+pub fn make_reachable(level: &mut Vec<Vec<Block>>, envelope: JumpEnvelope) -> bool {
+    if let Some(route) = route_ignoring_physics(level) {
+        carve_along_route(level, &route, envelope);
+    }
+    let mut exploration = explore(level, envelope);
+    //levels are generated while loading, so a maze that needs too much carving is given up on
+    let mut explorations_left = MAX_EXPLORATIONS;
+    loop {
+        if exploration.end.is_some() {
+            return true;
+        }
+        let route = match route_ignoring_physics(level) {
+            Some(route) => route,
+            None => return false,
+        };
+        let reached_until = route
+            .iter()
+            .position(|cell| !exploration.occupied.contains(cell))
+            .unwrap_or_else(|| route.len());
+        //footholds go before ledges, as they don't fill in any passages
+        let reached = &route[..reached_until];
+        let candidates = reached
+            .iter()
+            .rev()
+            .flat_map(|&cell| footholds(level, cell))
+            .map(|foothold| vec![(foothold, Block::Air)])
+            .chain(
+                reached
+                    .iter()
+                    .rev()
+                    .flat_map(|&cell| ledges(level, cell, &route))
+                    .map(|(ledge, below)| vec![(ledge, Block::Air), (below, Block::Dirt)]),
+            )
+            .collect::<Vec<_>>();
+        let mut changed = None;
+        for candidate in candidates {
+            let previous: Vec<_> = candidate
+                .iter()
+                .map(|&((x, y), _)| ((x, y), level[y][x]))
+                .collect();
+            for &((x, y), block) in &candidate {
+                level[y][x] = block;
+            }
+            //exploring the whole level again is slow, so only do it if something new can be reached nearby
+            if opens_up_nearby(level, &exploration, candidate[0].0, envelope) {
+                if explorations_left == 0 {
+                    return false;
+                }
+                explorations_left -= 1;
+                let after = explore(level, envelope);
+                if after.end.is_some() || after.standing.len() > exploration.standing.len() {
+                    changed = Some(after);
+                    break;
+                }
+            }
+            for ((x, y), block) in previous {
+                level[y][x] = block;
+            }
+        }
+        match changed {
+            Some(after) => exploration = after,
+            None => return false,
+        }
+    }
+}
+
+pub fn move_end_within_reach(level: &mut Vec<Vec<Block>>, envelope: JumpEnvelope) -> bool {
+    let exploration = explore(level, envelope);
+    let (start_x, start_y) = match exploration.start {
+        Some(start) => start,
+        None => return false,
+    };
+    //the standing cells come out of a hash map, so ties are broken by position to stay deterministic
+    let furthest = exploration
+        .standing
+        .keys()
+        .filter(|&&cell| cell != (start_x, start_y))
+        .max_by_key(|&&(x, y)| {
+            let distance =
+                (x as isize - start_x as isize).abs() + (y as isize - start_y as isize).abs();
+            (distance, y, x)
+        });
+    let (x, y) = match furthest {
+        Some(&cell) => cell,
+        None => return false,
+    };
+    for row in level.iter_mut() {
+        for block in row.iter_mut().filter(|block| **block == Block::PlayerEnd) {
+            *block = Block::Air;
+        }
+    }
+    level[y][x] = Block::PlayerEnd;
+    true
+}
+
+pub fn place_hazards(
+    level: &mut Vec<Vec<Block>>,
+    density: f64,
+    envelope: JumpEnvelope,
+    rng: &mut dyn RngCore,
+) {
+    let path = match solver::solve(level, envelope) {
+        Ok(solution) => solution.path(),
+        Err(_) => return,
+    };
+    let near_path: HashSet<Cell> = path
+        .iter()
+        .flat_map(|&(x, y)| {
+            (x.saturating_sub(1)..=x + 1).flat_map(move |near_x| {
+                (y.saturating_sub(1)..=y + 1).map(move |near_y| (near_x, near_y))
+            })
+        })
+        .collect();
+    let height = level.len();
+    for y in 1..height.saturating_sub(1) {
+        let width = level[y].len();
+        for x in 1..width.saturating_sub(1) {
+            if level[y][x] == Block::Dirt
+                && is_free(level, (x, y - 1))
+                && !near_path.contains(&(x, y))
+                && rng.gen_bool(density.max(0.).min(1.))
+            {
+                level[y][x] = Block::Spikes;
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use super::{Maze, MazeGenerator};
+use rand::{seq::SliceRandom, Rng, RngCore};
+
+pub struct Prim;
+
+impl MazeGenerator for Prim {
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let mut in_maze = vec![vec![false; maze.width()]; maze.height()];
+        let mut is_frontier = vec![vec![false; maze.width()]; maze.height()];
+        let mut frontier = Vec::new();
+        let start = maze.random_node(rng);
+        in_maze[start.1][start.0] = true;
+        for (x, y) in maze.neighbours(start) {
+            is_frontier[y][x] = true;
+            frontier.push((x, y));
+        }
+        while !frontier.is_empty() {
+            let node = frontier.swap_remove(rng.gen_range(0, frontier.len()));
+            let in_maze_neighbours: Vec<_> = maze
+                .neighbours(node)
+                .into_iter()
+                .filter(|&(x, y)| in_maze[y][x])
+                .collect();
+            let connect_to = *in_maze_neighbours
+                .choose(rng)
+                .expect("frontier nodes always border the maze");
+            maze.open(node, connect_to);
+            in_maze[node.1][node.0] = true;
+            for (x, y) in maze.neighbours(node) {
+                if !in_maze[y][x] && !is_frontier[y][x] {
+                    is_frontier[y][x] = true;
+                    frontier.push((x, y));
+                }
+            }
+        }
+    }
+}
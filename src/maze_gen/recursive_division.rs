@@ -0,0 +1,45 @@
+use super::{Maze, MazeGenerator};
+use rand::{Rng, RngCore};
+
+pub struct RecursiveDivision;
+
+impl MazeGenerator for RecursiveDivision {
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        for node in maze.nodes().collect::<Vec<_>>() {
+            for other in maze.neighbours(node) {
+                maze.open(node, other);
+            }
+        }
+        //(x, y, width, height) of every area that still needs to be divided
+        let mut areas = vec![(0, 0, maze.width(), maze.height())];
+        while let Some((x, y, width, height)) = areas.pop() {
+            if width < 2 || height < 2 {
+                continue;
+            }
+            let split_horizontally = if width == height {
+                rng.gen_bool(0.5)
+            } else {
+                height > width
+            };
+            if split_horizontally {
+                //the wall goes below row `y + offset`
+                let offset = rng.gen_range(0, height - 1);
+                let gap = rng.gen_range(x, x + width);
+                for wall_x in (x..x + width).filter(|&wall_x| wall_x != gap) {
+                    maze.close((wall_x, y + offset), (wall_x, y + offset + 1));
+                }
+                areas.push((x, y, width, offset + 1));
+                areas.push((x, y + offset + 1, width, height - offset - 1));
+            } else {
+                //the wall goes to the right of column `x + offset`
+                let offset = rng.gen_range(0, width - 1);
+                let gap = rng.gen_range(y, y + height);
+                for wall_y in (y..y + height).filter(|&wall_y| wall_y != gap) {
+                    maze.close((x + offset, wall_y), (x + offset + 1, wall_y));
+                }
+                areas.push((x, y, offset + 1, height));
+                areas.push((x + offset + 1, y, width - offset - 1, height));
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use super::{Maze, MazeGenerator};
+use rand::{seq::SliceRandom, RngCore};
+
+pub struct Wilson;
+
+impl MazeGenerator for Wilson {
+    fn carve(&self, maze: &mut Maze, rng: &mut dyn RngCore) {
+        let mut in_maze = vec![vec![false; maze.width()]; maze.height()];
+        let first = maze.random_node(rng);
+        in_maze[first.1][first.0] = true;
+        let mut remaining: Vec<_> = maze.nodes().filter(|&node| node != first).collect();
+        remaining.shuffle(rng);
+        let mut next_step = vec![vec![None; maze.width()]; maze.height()];
+        for start in remaining {
+            if in_maze[start.1][start.0] {
+                continue;
+            }
+            //walk until we hit the maze, only remembering the last direction taken from every node
+            //so loops erase themselves
+            let mut current = start;
+            while !in_maze[current.1][current.0] {
+                let next = *maze
+                    .neighbours(current)
+                    .choose(rng)
+                    .expect("every node has a neighbour");
+                next_step[current.1][current.0] = Some(next);
+                current = next;
+            }
+            let mut current = start;
+            while !in_maze[current.1][current.0] {
+                let next = next_step[current.1][current.0].expect("the walk went through here");
+                maze.open(current, next);
+                in_maze[current.1][current.0] = true;
+                current = next;
+            }
+        }
+    }
+}
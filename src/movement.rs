@@ -0,0 +1,233 @@
+use crate::{tuning::Tuning, Block};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+pub const BLOCK_SIZE: usize = 32;
+
+pub type Cell = (usize, usize);
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct JumpEnvelope {
+    pub max_climb: usize,
+    pub max_drift: usize,
+}
+
+impl JumpEnvelope {
+    pub fn from_physics(tuning: &Tuning, jumps: u32, block_size: f64) -> Self {
+        let single_jump = tuning.jump_speed * tuning.jump_speed / (2. * tuning.gravity);
+        let rising = tuning.jump_speed / tuning.gravity * f64::from(jumps);
+        let speeding_up = (tuning.top_speed / tuning.air_acceleration).min(rising);
+        let drift = tuning.air_acceleration * speeding_up * speeding_up / 2.
+            + tuning.top_speed * (rising - speeding_up);
+        Self {
+            max_climb: (single_jump * f64::from(jumps) / block_size).floor() as usize,
+            max_drift: (drift / block_size).floor() as usize,
+        }
+    }
+}
+
+pub fn jump_envelope(tuning: &Tuning, jumps: u32) -> JumpEnvelope {
+    JumpEnvelope::from_physics(tuning, jumps, BLOCK_SIZE as f64)
+}
+
+fn block_at(level: &[Vec<Block>], (x, y): Cell) -> Option<Block> {
+    level.get(y).and_then(|row| row.get(x)).copied()
+}
+
+pub fn is_free(level: &[Vec<Block>], cell: Cell) -> bool {
+    block_at(level, cell)
+        .map(|block| !block.is_colideable())
+        .unwrap_or(false)
+}
+
+pub fn has_floor(level: &[Vec<Block>], (x, y): Cell) -> bool {
+    !is_free(level, (x, y + 1))
+}
+
+pub fn touches_end(level: &[Vec<Block>], cell: Cell) -> bool {
+    touches(level, cell, Block::PlayerEnd)
+}
+
+pub fn touches(level: &[Vec<Block>], (x, y): Cell, block: Block) -> bool {
+    let mut around = vec![(x + 1, y), (x, y + 1)];
+    if x > 0 {
+        around.push((x - 1, y));
+    }
+    if y > 0 {
+        around.push((x, y - 1));
+    }
+    around
+        .into_iter()
+        .any(|cell| block_at(level, cell) == Some(block))
+}
+
+pub fn fall(level: &[Vec<Block>], from: Cell) -> Vec<Cell> {
+    let mut cells = Vec::new();
+    let mut current = from;
+    while !has_floor(level, current) {
+        current = (current.0, current.1 + 1);
+        cells.push(current);
+    }
+    cells
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Move {
+    pub path: Vec<Cell>,
+}
+
+impl Move {
+    pub fn to(&self) -> Option<Cell> {
+        self.path.last().copied()
+    }
+}
+
+pub fn moves(level: &[Vec<Block>], from: Cell, envelope: JumpEnvelope) -> Vec<Move> {
+    //(cell, blocks risen, blocks drifted) so cells can be visited again with more of the envelope left
+    type State = (Cell, usize, usize);
+    let mut came_from: HashMap<State, State> = HashMap::new();
+    let mut queue = VecDeque::new();
+    let start = (from, 0, 0);
+    queue.push_back(start);
+    let mut reached_cells = HashSet::new();
+    let mut moves = Vec::new();
+    while let Some(state) = queue.pop_front() {
+        let ((x, y), risen, drifted) = state;
+        if state != start && reached_cells.insert((x, y)) {
+            let mut path = vec![(x, y)];
+            let mut current = state;
+            while let Some(&previous) = came_from.get(&current) {
+                if previous != start {
+                    path.push(previous.0);
+                }
+                current = previous;
+            }
+            path.reverse();
+            path.extend(fall(level, (x, y)));
+            moves.push(Move { path });
+        }
+        //falling doesn't use up any of the envelope, the player can steer while doing it
+        let mut next = vec![((x, y + 1), risen, drifted)];
+        if risen < envelope.max_climb && y > 0 {
+            next.push(((x, y - 1), risen + 1, drifted));
+        }
+        if drifted < envelope.max_drift {
+            next.push(((x + 1, y), risen, drifted + 1));
+            if x > 0 {
+                next.push(((x - 1, y), risen, drifted + 1));
+            }
+        }
+        for next in next {
+            if is_free(level, next.0) && !came_from.contains_key(&next) {
+                came_from.insert(next, state);
+                queue.push_back(next);
+            }
+        }
+    }
+    moves
+}
+
+pub struct Exploration {
+    pub start: Option<Cell>,
+    pub standing: HashMap<Cell, Option<(Cell, Move)>>,
+    pub occupied: HashSet<Cell>,
+    pub end: Option<(Cell, Move)>,
+}
+
+pub fn find_start(level: &[Vec<Block>]) -> Option<Cell> {
+    level.iter().enumerate().find_map(|(y, row)| {
+        row.iter()
+            .position(|block| *block == Block::PlayerStart)
+            .map(|x| (x, y))
+    })
+}
+
+pub fn explore(level: &[Vec<Block>], envelope: JumpEnvelope) -> Exploration {
+    match find_start(level) {
+        Some(start) => explore_from(level, start, envelope),
+        None => Exploration {
+            start: None,
+            standing: HashMap::new(),
+            occupied: HashSet::new(),
+            end: None,
+        },
+    }
+}
+
+pub fn explore_from(level: &[Vec<Block>], start: Cell, envelope: JumpEnvelope) -> Exploration {
+    let mut exploration = Exploration {
+        start: Some(start),
+        standing: HashMap::new(),
+        occupied: HashSet::new(),
+        end: None,
+    };
+    //(cell, blocks risen, blocks drifted) with every landing starting over with the whole envelope,
+    //so each of them only has to be visited once for the whole level
+    type State = (Cell, usize, usize);
+    let width = level.iter().map(Vec::len).max().unwrap_or(0);
+    let (climbs, drifts) = (envelope.max_climb + 1, envelope.max_drift + 1);
+    let index =
+        |((x, y), risen, drifted): State| ((y * width + x) * climbs + risen) * drifts + drifted;
+    let mut came_from: Vec<Option<State>> = vec![None; level.len() * width * climbs * drifts];
+    //the player can still jump and steer while falling down from the start, so it counts as standing
+    let first = (start, 0, 0);
+    let is_standing = |(cell, _, _): State| cell == start || has_floor(level, cell);
+    let step_to = |came_from: &[Option<State>], state: State| {
+        let mut path = vec![state.0];
+        let mut current = state;
+        while current != first && !(current != state && is_standing(current)) {
+            current = came_from[index(current)].expect("every queued state has been reached");
+            path.push(current.0);
+        }
+        path.pop();
+        path.reverse();
+        if path.is_empty() {
+            path.push(state.0);
+        }
+        (current.0, Move { path })
+    };
+    came_from[index(first)] = Some(first);
+    exploration.occupied.insert(start);
+    exploration.standing.insert(start, None);
+    let mut queue = VecDeque::new();
+    queue.push_back(first);
+    while let Some(state) = queue.pop_front() {
+        let ((x, y), risen, drifted) = state;
+        if touches_end(level, (x, y)) {
+            exploration.end = Some(step_to(&came_from, state));
+            return exploration;
+        }
+        //falling doesn't use up any of the envelope, the player can steer while doing it
+        let next = [
+            Some(((x, y + 1), risen, drifted)),
+            y.checked_sub(1)
+                .filter(|_| risen < envelope.max_climb)
+                .map(|up| ((x, up), risen + 1, drifted)),
+            Some(((x + 1, y), risen, drifted + 1)).filter(|_| drifted < envelope.max_drift),
+            x.checked_sub(1)
+                .filter(|_| drifted < envelope.max_drift)
+                .map(|left| ((left, y), risen, drifted + 1)),
+        ];
+        for &(cell, risen, drifted) in next.iter().flatten() {
+            //touching spikes restarts the level, so there is no point in going on from there
+            if !is_free(level, cell) || touches(level, cell, Block::Spikes) {
+                continue;
+            }
+            let next = if has_floor(level, cell) {
+                (cell, 0, 0)
+            } else {
+                (cell, risen, drifted)
+            };
+            if came_from[index(next)].is_some() {
+                continue;
+            }
+            came_from[index(next)] = Some(state);
+            exploration.occupied.insert(cell);
+            if is_standing(next) && !exploration.standing.contains_key(&cell) {
+                let step = step_to(&came_from, next);
+                exploration.standing.insert(cell, Some(step));
+            }
+            queue.push_back(next);
+        }
+    }
+    exploration
+}
@@ -0,0 +1,6 @@
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Options {
+    pub fullscreen: bool,
+    pub speedrun: bool,
+    pub ghost: bool,
+}
@@ -0,0 +1,284 @@
+use crate::save::APP_NAME;
+use quicksilver::{
+    log,
+    saving::{load_raw, save_raw, Location},
+};
+use std::{
+    convert::TryInto,
+    fmt::{self, Display, Formatter},
+};
+
+const MAGIC: &[u8; 4] = b"HHRP";
+pub const VERSION: u16 = 1;
+
+const JUMP_PRESSED: u8 = 1;
+const JUMP_RELEASED: u8 = 2;
+const FLIP: u8 = 4;
+const SWAP: u8 = 8;
+const LET_GO: u8 = 16;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct TickInput {
+    /// From -127 (full speed left) up to 127 (full speed right).
+    pub direction: i8,
+    pub jump_pressed: bool,
+    pub jump_released: bool,
+    pub flip: bool,
+    pub swap: bool,
+    pub let_go: bool,
+}
+
+impl TickInput {
+    pub fn set_direction(&mut self, direction: f64) {
+        self.direction = (direction.max(-1.).min(1.) * 127.).round() as i8;
+    }
+    pub fn direction(self) -> f64 {
+        f64::from(self.direction) / 127.
+    }
+    fn buttons(self) -> u8 {
+        [
+            (self.jump_pressed, JUMP_PRESSED),
+            (self.jump_released, JUMP_RELEASED),
+            (self.flip, FLIP),
+            (self.swap, SWAP),
+            (self.let_go, LET_GO),
+        ]
+        .iter()
+        .filter(|(on, _)| *on)
+        .fold(0, |buttons, (_, bit)| buttons | bit)
+    }
+    fn from_parts(direction: i8, buttons: u8) -> Self {
+        Self {
+            direction,
+            jump_pressed: buttons & JUMP_PRESSED != 0,
+            jump_released: buttons & JUMP_RELEASED != 0,
+            flip: buttons & FLIP != 0,
+            swap: buttons & SWAP != 0,
+            let_go: buttons & LET_GO != 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Replay {
+    pub run_seed: u64,
+    pub level: u32,
+    pub tuning_hash: u64,
+    pub inputs: Vec<TickInput>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReplayError {
+    NotAReplay,
+    UnsupportedVersion(u16),
+    Truncated,
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::NotAReplay => write!(f, "not a replay"),
+            ReplayError::UnsupportedVersion(version) => write!(
+                f,
+                "replay is version {} while only version {} can be played",
+                version, VERSION
+            ),
+            ReplayError::Truncated => write!(f, "replay ends too early"),
+        }
+    }
+}
+
+fn take<'a>(bytes: &mut &'a [u8], count: usize) -> Result<&'a [u8], ReplayError> {
+    if bytes.len() < count {
+        return Err(ReplayError::Truncated);
+    }
+    let (taken, rest) = bytes.split_at(count);
+    *bytes = rest;
+    Ok(taken)
+}
+
+impl Replay {
+    fn last_profile(run_seed: u64, level: u32) -> String {
+        format!("replay_{}_{}", run_seed, level)
+    }
+    fn best_profile(run_seed: u64, level: u32) -> String {
+        format!("best_{}_{}", run_seed, level)
+    }
+    fn save_as(&self, profile: &str) {
+        if let Err(err) = save_raw(Location::Data, APP_NAME, profile, &self.to_bytes()) {
+            log::error!("Could not save the replay: {}", err);
+        }
+    }
+    pub fn save(&self) {
+        self.save_as(&Self::last_profile(self.run_seed, self.level));
+    }
+    pub fn save_best(&self) {
+        self.save_as(&Self::best_profile(self.run_seed, self.level));
+    }
+    pub fn load(path: &str) -> Option<Self> {
+        match std::fs::read(path) {
+            Ok(bytes) => Self::parse_logged(path, &bytes),
+            Err(err) => {
+                log::warn!("Could not load the replay {}: {}", path, err);
+                None
+            }
+        }
+    }
+    pub fn load_best(run_seed: u64, level: u32) -> Option<Self> {
+        let profile = Self::best_profile(run_seed, level);
+        //not having one is normal, so only broken ones get logged
+        let bytes = load_raw(Location::Data, APP_NAME, &profile).ok()?;
+        Self::parse_logged(&profile, &bytes)
+    }
+    fn parse_logged(name: &str, bytes: &[u8]) -> Option<Self> {
+        match Self::from_bytes(bytes) {
+            Ok(replay) => Some(replay),
+            Err(err) => {
+                log::warn!("Could not load the replay {}: {}", name, err);
+                None
+            }
+        }
+    }
+    pub fn from_args() -> Option<Self> {
+        let mut args = std::env::args().skip_while(|arg| arg != "--replay").skip(1);
+        args.next().and_then(|path| Self::load(&path))
+    }
+    /// `HHRP`, the version, seed, level, tuning hash and amount of runs followed by the runs, little endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut runs: Vec<(u16, TickInput)> = Vec::new();
+        for input in &self.inputs {
+            match runs.last_mut() {
+                Some((count, last)) if last == input && *count < u16::MAX => *count += 1,
+                _ => runs.push((1, *input)),
+            }
+        }
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.run_seed.to_le_bytes());
+        bytes.extend_from_slice(&self.level.to_le_bytes());
+        bytes.extend_from_slice(&self.tuning_hash.to_le_bytes());
+        bytes.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+        for (count, input) in runs {
+            bytes.extend_from_slice(&count.to_le_bytes());
+            bytes.extend_from_slice(&input.direction.to_le_bytes());
+            bytes.push(input.buttons());
+        }
+        bytes
+    }
+    pub fn from_bytes(mut bytes: &[u8]) -> Result<Self, ReplayError> {
+        let bytes = &mut bytes;
+        if bytes.len() < MAGIC.len() || take(bytes, MAGIC.len())? != MAGIC {
+            return Err(ReplayError::NotAReplay);
+        }
+        //the lengths are always right, as `take` returns exactly as many bytes as asked for
+        let version = u16::from_le_bytes(take(bytes, 2)?.try_into().expect("2 bytes"));
+        if version != VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+        let run_seed = u64::from_le_bytes(take(bytes, 8)?.try_into().expect("8 bytes"));
+        let level = u32::from_le_bytes(take(bytes, 4)?.try_into().expect("4 bytes"));
+        let tuning_hash = u64::from_le_bytes(take(bytes, 8)?.try_into().expect("8 bytes"));
+        let runs = u32::from_le_bytes(take(bytes, 4)?.try_into().expect("4 bytes"));
+        let mut inputs = Vec::new();
+        for _ in 0..runs {
+            let run = take(bytes, 4)?;
+            let count = u16::from_le_bytes([run[0], run[1]]);
+            let input = TickInput::from_parts(run[2] as i8, run[3]);
+            inputs.extend(std::iter::repeat(input).take(count.into()));
+        }
+        Ok(Self {
+            run_seed,
+            level,
+            tuning_hash,
+            inputs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay(inputs: Vec<TickInput>) -> Replay {
+        Replay {
+            run_seed: 0xDEAD_BEEF_1234,
+            level: 7,
+            tuning_hash: 0x0123_4567_89AB_CDEF,
+            inputs,
+        }
+    }
+
+    fn walking(direction: i8) -> TickInput {
+        TickInput {
+            direction,
+            ..TickInput::default()
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut inputs = vec![TickInput::default(); 3];
+        inputs.push(TickInput {
+            direction: -127,
+            jump_pressed: true,
+            flip: true,
+            ..TickInput::default()
+        });
+        inputs.push(TickInput {
+            jump_released: true,
+            swap: true,
+            let_go: true,
+            ..TickInput::default()
+        });
+        inputs.extend(vec![walking(64); 10]);
+        let replay = replay(inputs);
+        assert_eq!(Replay::from_bytes(&replay.to_bytes()), Ok(replay));
+        let empty = self::replay(Vec::new());
+        assert_eq!(Replay::from_bytes(&empty.to_bytes()), Ok(empty));
+    }
+
+    #[test]
+    fn round_trip_of_long_runs() {
+        let max = usize::from(u16::MAX);
+        for length in &[max - 1, max, max + 1, 2 * max + 1] {
+            let replay = replay(vec![walking(127); *length]);
+            let bytes = replay.to_bytes();
+            //every run is 4 bytes, after the 30 bytes of the header
+            let runs = (length + max - 1) / max;
+            assert_eq!(bytes.len(), 30 + runs * 4);
+            assert_eq!(Replay::from_bytes(&bytes), Ok(replay));
+        }
+    }
+
+    #[test]
+    fn unknown_version() {
+        let mut bytes = replay(vec![walking(1)]).to_bytes();
+        bytes[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert_eq!(
+            Replay::from_bytes(&bytes),
+            Err(ReplayError::UnsupportedVersion(VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn bad_magic() {
+        let mut bytes = replay(vec![walking(1)]).to_bytes();
+        bytes[0] = b'X';
+        assert_eq!(Replay::from_bytes(&bytes), Err(ReplayError::NotAReplay));
+        assert_eq!(Replay::from_bytes(b"HH"), Err(ReplayError::NotAReplay));
+        assert_eq!(Replay::from_bytes(&[]), Err(ReplayError::NotAReplay));
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = replay(vec![walking(1), walking(2)]).to_bytes();
+        for length in MAGIC.len()..bytes.len() {
+            assert_eq!(
+                Replay::from_bytes(&bytes[..length]),
+                Err(ReplayError::Truncated),
+                "cut off after {} bytes",
+                length
+            );
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::options::Options;
+use quicksilver::{
+    log,
+    saving::{load_raw, save_raw, Location},
+};
+use std::collections::BTreeMap;
+
+pub const APP_NAME: &str = "heaven_and_hell";
+const SAVE_PROFILE: &str = "save";
+
+pub const VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Records {
+    pub best_times: BTreeMap<u32, u32>,
+    pub deaths: BTreeMap<u32, u32>,
+}
+
+impl Records {
+    pub fn finished(&mut self, level: u32, updates: u32) -> bool {
+        let best = self.best_times.entry(level).or_insert(updates);
+        if updates <= *best {
+            *best = updates;
+            true
+        } else {
+            false
+        }
+    }
+    pub fn died(&mut self, level: u32) {
+        *self.deaths.entry(level).or_insert(0) += 1;
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SaveData {
+    pub reached_level: Option<u32>,
+    pub run_seed: u64,
+    pub records: Records,
+    pub options: Options,
+}
+
+impl SaveData {
+    pub fn load() -> Self {
+        match load_raw(Location::Data, APP_NAME, SAVE_PROFILE) {
+            Ok(saved) => Self::parse(&String::from_utf8_lossy(&saved)),
+            Err(_) => Self::default(),
+        }
+    }
+    pub fn save(&self) {
+        if let Err(err) = save_raw(
+            Location::Data,
+            APP_NAME,
+            SAVE_PROFILE,
+            self.to_string().as_bytes(),
+        ) {
+            log::error!("Could not save the game: {}", err);
+        }
+    }
+    fn parse(saved: &str) -> Self {
+        let mut lines = saved.lines().filter(|line| !line.trim().is_empty());
+        let mut first = None;
+        if let Some(line) = lines.next() {
+            match line.split_whitespace().collect::<Vec<_>>().as_slice() {
+                ["version", number] => match number.parse::<u32>() {
+                    Ok(version) if version > VERSION => log::warn!(
+                        "The save is from a newer version ({}, this is {}), some of it might not load",
+                        version,
+                        VERSION
+                    ),
+                    Ok(_) => (),
+                    Err(_) => log::warn!("The save has a broken version: {}", line),
+                },
+                _ => first = Some(line),
+            }
+        }
+        let mut save = Self::default();
+        for line in first.into_iter().chain(lines) {
+            if save.set(line).is_none() {
+                log::warn!("The save has a line that could not be loaded: {}", line);
+            }
+        }
+        save
+    }
+    fn set(&mut self, line: &str) -> Option<()> {
+        let parts: Vec<_> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            ["reached", level] => self.reached_level = Some(level.parse().ok()?),
+            ["seed", seed] => self.run_seed = seed.parse().ok()?,
+            ["fullscreen", on] => self.options.fullscreen = on.parse().ok()?,
+            ["speedrun", on] => self.options.speedrun = on.parse().ok()?,
+            ["ghost", on] => self.options.ghost = on.parse().ok()?,
+            ["best", level, updates] => {
+                let level = level.parse().ok()?;
+                self.records.best_times.insert(level, updates.parse().ok()?);
+            }
+            ["deaths", level, deaths] => {
+                let level = level.parse().ok()?;
+                self.records.deaths.insert(level, deaths.parse().ok()?);
+            }
+            _ => return None,
+        }
+        Some(())
+    }
+}
+
+impl std::fmt::Display for SaveData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "version {}", VERSION)?;
+        if let Some(level) = self.reached_level {
+            writeln!(f, "reached {}", level)?;
+        }
+        writeln!(f, "seed {}", self.run_seed)?;
+        writeln!(f, "fullscreen {}", self.options.fullscreen)?;
+        writeln!(f, "speedrun {}", self.options.speedrun)?;
+        writeln!(f, "ghost {}", self.options.ghost)?;
+        for (level, updates) in &self.records.best_times {
+            writeln!(f, "best {} {}", level, updates)?;
+        }
+        for (level, deaths) in &self.records.deaths {
+            writeln!(f, "deaths {} {}", level, deaths)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut save = SaveData {
+            reached_level: Some(4),
+            run_seed: 1234,
+            records: Records::default(),
+            options: Options {
+                fullscreen: true,
+                speedrun: false,
+                ghost: true,
+            },
+        };
+        save.records.finished(1, 300);
+        save.records.finished(2, 450);
+        save.records.died(2);
+        save.records.died(2);
+        assert_eq!(SaveData::parse(&save.to_string()), save);
+        assert_eq!(
+            SaveData::parse(&SaveData::default().to_string()),
+            SaveData::default()
+        );
+    }
+
+    #[test]
+    fn broken_lines_are_skipped() {
+        let save = SaveData::parse("version 1\nreached three\nseed 5\nbest 1\n");
+        assert_eq!(save.reached_level, None);
+        assert_eq!(save.run_seed, 5);
+        assert!(save.records.best_times.is_empty());
+    }
+}
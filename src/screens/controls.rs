@@ -0,0 +1,193 @@
+use crate::{
+    gamepad::{MenuStep, PadEvent},
+    input::{is_bindable, key_name, Action, Bindings},
+    screens::{add_button, relabel_button, MenuNavigation},
+    Screen, Transition, Wrapper,
+};
+use mergui::{
+    channels::{BasicClickable, Clickable},
+    LayerId, Response,
+};
+use quicksilver::{
+    geom::{Rectangle, Transform, Vector},
+    graphics::Color,
+    lifecycle::{Event, Key},
+    Result,
+};
+
+use async_trait::async_trait;
+
+struct Row {
+    action: Action,
+    bind: Response<BasicClickable>,
+    clear: Response<BasicClickable>,
+}
+
+const DEFAULTS: Rectangle = Rectangle {
+    pos: Vector { x: 20., y: 560. },
+    size: Vector { x: 290., y: 50. },
+};
+const BACK: Rectangle = Rectangle {
+    pos: Vector { x: 330., y: 560. },
+    size: Vector { x: 290., y: 50. },
+};
+
+fn row_locations(index: usize) -> (Rectangle, Rectangle) {
+    let y = 80 + index as i32 * 58;
+    (
+        Rectangle::new((20, y), (470, 50)),
+        Rectangle::new((500, y), (120, 50)),
+    )
+}
+
+pub struct Controls {
+    layer: LayerId,
+    rows: Vec<Row>,
+    defaults: Response<BasicClickable>,
+    back: Response<BasicClickable>,
+    listening: Option<Action>,
+    menu: MenuNavigation,
+}
+
+impl Controls {
+    pub(crate) fn new(wrapper: &mut Wrapper<'_>) -> Self {
+        let layer = wrapper.context.add_layer();
+        let rows = Self::add_rows(wrapper, &layer);
+        let defaults = add_button(wrapper, &layer, "Defaults", DEFAULTS);
+        let back = add_button(wrapper, &layer, "Back", BACK);
+        let mut buttons = Vec::new();
+        for index in 0..Action::ALL.len() {
+            let (bind, clear) = row_locations(index);
+            buttons.push(bind);
+            buttons.push(clear);
+        }
+        buttons.push(DEFAULTS);
+        buttons.push(BACK);
+        Self {
+            layer,
+            rows,
+            defaults,
+            back,
+            listening: None,
+            menu: MenuNavigation::new(buttons),
+        }
+    }
+    fn add_rows(wrapper: &mut Wrapper<'_>, layer: &LayerId) -> Vec<Row> {
+        Action::ALL
+            .iter()
+            .enumerate()
+            .map(|(index, action)| {
+                let text = bind_text(wrapper, *action);
+                let (bind, clear) = row_locations(index);
+                Row {
+                    action: *action,
+                    bind: add_button(wrapper, layer, &text, bind),
+                    clear: add_button(wrapper, layer, "Clear", clear),
+                }
+            })
+            .collect()
+    }
+    fn changed(&mut self, wrapper: &mut Wrapper<'_>) {
+        wrapper.bindings.save();
+        for (index, row) in self.rows.iter_mut().enumerate() {
+            let text = bind_text(wrapper, row.action);
+            relabel_button(
+                &mut row.bind,
+                wrapper,
+                &self.layer,
+                &text,
+                row_locations(index).0,
+            );
+        }
+    }
+}
+
+fn bind_text(wrapper: &Wrapper<'_>, action: Action) -> String {
+    let keys: Vec<_> = wrapper
+        .bindings
+        .keys(action)
+        .iter()
+        .map(|key| key_name(*key))
+        .collect();
+    let keys = if keys.is_empty() {
+        String::from("-")
+    } else {
+        keys.join(", ")
+    };
+    format!("{}: {}", action.label(), keys)
+}
+
+#[async_trait(?Send)]
+impl Screen for Controls {
+    async fn draw(&mut self, wrapper: &mut Wrapper<'_>) -> Result<()> {
+        wrapper.gfx.set_transform(Transform::IDENTITY);
+        wrapper.gfx.clear(Color::BLACK);
+        self.menu.draw(wrapper);
+        let text = match self.listening {
+            Some(action) => format!(
+                "Press a key for {}, or Escape to cancel.",
+                action.label().to_lowercase()
+            ),
+            None => String::from("Click an action to add a key to it."),
+        };
+        wrapper.draw_small_text(&text, Vector::new(20, 50))
+    }
+    async fn update(&mut self, wrapper: &mut Wrapper<'_>) -> Result<Option<Transition>> {
+        let mut changed = false;
+        for (index, row) in self.rows.iter_mut().enumerate() {
+            let (bind, clear) = row_locations(index);
+            if row.bind.channel.has_clicked() || self.menu.pressed(bind) {
+                self.listening = Some(row.action);
+            }
+            if row.clear.channel.has_clicked() || self.menu.pressed(clear) {
+                wrapper.bindings.clear(row.action);
+                changed = true;
+            }
+        }
+        if self.defaults.channel.has_clicked() || self.menu.pressed(DEFAULTS) {
+            wrapper.bindings = Bindings::default();
+            changed = true;
+        }
+        if changed {
+            self.changed(wrapper);
+        }
+        if self.back.channel.has_clicked() || self.menu.pressed(BACK) {
+            return Ok(Some(Transition::Pop));
+        }
+        Ok(None)
+    }
+    async fn event(
+        &mut self,
+        wrapper: &mut Wrapper<'_>,
+        event: &Event,
+    ) -> Result<Option<Transition>> {
+        if let (Some(action), Event::KeyboardInput(x)) = (self.listening, event) {
+            if !x.is_down() {
+                return Ok(None);
+            }
+            //Escape always pauses, so it can't be given to anything else
+            if x.key() == Key::Escape {
+                self.listening = None;
+            } else if is_bindable(x.key()) {
+                wrapper.bindings.bind(action, x.key());
+                self.listening = None;
+                self.changed(wrapper);
+            }
+        }
+        Ok(None)
+    }
+    async fn pad_event(
+        &mut self,
+        _wrapper: &mut Wrapper<'_>,
+        _event: &PadEvent,
+        step: Option<MenuStep>,
+    ) -> Result<Option<Transition>> {
+        if let Some(step) = step {
+            //going back while listening only stops listening, like Escape does
+            if self.menu.step(step) && self.listening.take().is_none() {
+                return Ok(Some(Transition::Pop));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{
+    gamepad::{MenuStep, PadEvent},
+    input::{key_names, Action},
+    screens::{game::Game, title::Title},
+    Screen, Transition, Wrapper,
+};
+use quicksilver::{
+    geom::{Transform, Vector},
+    graphics::Color,
+    lifecycle::Event,
+    Result,
+};
+
+use async_trait::async_trait;
+
+pub struct ErrorScreen {
+    message: String,
+    level: u32,
+}
+
+impl ErrorScreen {
+    pub(crate) fn new(message: String, level: u32) -> Self {
+        Self { message, level }
+    }
+}
+
+#[async_trait(?Send)]
+impl Screen for ErrorScreen {
+    async fn draw(&mut self, wrapper: &mut Wrapper<'_>) -> Result<()> {
+        wrapper.gfx.set_transform(Transform::IDENTITY);
+        wrapper.gfx.clear(Color::BLACK);
+        wrapper.draw_small_text(&self.message, Vector::new(20, 40))?;
+        let help = format!(
+            "Press {} to try again or {} to go back to the title screen.",
+            key_names(&wrapper.bindings, Action::Restart),
+            key_names(&wrapper.bindings, Action::Pause)
+        );
+        wrapper.draw_small_text(&help, Vector::new(20, 580))
+    }
+    async fn update(&mut self, _wrapper: &mut Wrapper<'_>) -> Result<Option<Transition>> {
+        Ok(None)
+    }
+    async fn event(
+        &mut self,
+        wrapper: &mut Wrapper<'_>,
+        event: &Event,
+    ) -> Result<Option<Transition>> {
+        if let Event::KeyboardInput(x) = event {
+            if !x.is_down() {
+                return Ok(None);
+            }
+            match wrapper.bindings.action_for(x.key()) {
+                Some(Action::Restart) => {
+                    return Ok(Some(Transition::Replace(
+                        Game::load(wrapper, self.level).await?,
+                    )))
+                }
+                Some(Action::Pause) => {
+                    return Ok(Some(Transition::ClearTo(Box::new(Title::new(wrapper)))))
+                }
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+    async fn pad_event(
+        &mut self,
+        wrapper: &mut Wrapper<'_>,
+        _event: &PadEvent,
+        step: Option<MenuStep>,
+    ) -> Result<Option<Transition>> {
+        match step {
+            Some(MenuStep::Confirm) => Ok(Some(Transition::Replace(
+                Game::load(wrapper, self.level).await?,
+            ))),
+            Some(MenuStep::Back) => Ok(Some(Transition::ClearTo(Box::new(Title::new(wrapper))))),
+            _ => Ok(None),
+        }
+    }
+}
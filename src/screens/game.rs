@@ -0,0 +1,935 @@
+use crate::{
+    contacts::Contacts,
+    gamepad::{MenuStep, PadEvent},
+    input::{key_names, Action},
+    jump::JumpController,
+    levels::{Level, World},
+    loading::loading_screen,
+    movement::{self, jump_envelope, Cell, BLOCK_SIZE},
+    replay::{Replay, TickInput},
+    screens::{error::ErrorScreen, pause::Pause, results::Results},
+    seeds,
+    solver::{self, Unsolvable},
+    timer::{format_time, Split},
+    timestep::UPDATES_PER_SECOND,
+    tuning::Tuning,
+    Block, Screen, Transition, Wrapper,
+};
+use quicksilver::{
+    geom::{Rectangle, Transform, Vector},
+    graphics::Color,
+    lifecycle::{Event, GamepadAxis},
+    Result,
+};
+
+use async_trait::async_trait;
+
+extern crate nalgebra as na;
+
+use na::Vector2 as V2;
+use ncollide2d::pipeline::CollisionGroups;
+use ncollide2d::shape::ShapeHandle;
+use nphysics2d::force_generator::DefaultForceGeneratorSet;
+use nphysics2d::joint::DefaultJointConstraintSet;
+use nphysics2d::material::{BasicMaterial, MaterialCombineMode, MaterialHandle};
+use nphysics2d::object::ColliderDesc;
+use nphysics2d::object::{
+    BodyPartHandle, BodyStatus, DefaultBodySet, DefaultColliderHandle, DefaultColliderSet,
+    RigidBody, RigidBodyDesc,
+};
+use nphysics2d::world::{DefaultGeometricalWorld, DefaultMechanicalWorld};
+
+use rand::Rng;
+
+const CAMERA_LEAN: f32 = 96.;
+const CAMERA_LEAN_SPEED: f32 = 0.05;
+const CAMERA_LEAN_FALLING_SPEED: f64 = 200.;
+
+const PLAYER_COLLISION_GROUP: usize = 2;
+
+const PLAYER_WIDTH: i32 = 16;
+const PLAYER_HEIGHT: i32 = 32;
+
+pub struct Game {
+    player_pos: Vector,
+    momentum: Vector,
+    jump: JumpController,
+    tuning: Tuning,
+    mechanical_world: DefaultMechanicalWorld<f64>,
+    previous_physics_pos: V2<f64>,
+    geometrical_world: DefaultGeometricalWorld<f64>,
+    bodies: DefaultBodySet<f64>,
+    colliders: DefaultColliderSet<f64>,
+    joint_constraints: DefaultJointConstraintSet<f64>,
+    force_generators: DefaultForceGeneratorSet<f64>,
+    player_body: DefaultColliderHandle,
+    level_as_colliders: Vec<(World, DefaultColliderHandle)>,
+    jump_count: u32,
+    max_jumps: u32,
+    is_flying: bool,
+    contacts: Contacts,
+    gravity_inverted: bool,
+    can_flip: bool,
+    camera_lean: f32,
+    render_going_to_left: bool,
+    current_level: u32,
+    stars: Vec<Rectangle>,
+    level_size: Vector,
+    level: Level,
+    world: World,
+    //set when swapping worlds until no portal is touched anymore, so a portal in both worlds doesn't swap back
+    in_portal: bool,
+    hint: Option<std::result::Result<Vec<Cell>, Unsolvable>>,
+    updates: u32,
+    queued: TickInput,
+    recorded: Vec<TickInput>,
+    playback: Option<Replay>,
+    ghost: Option<Box<Game>>,
+}
+
+impl Game {
+    pub(crate) async fn load(
+        wrapper: &mut Wrapper<'_>,
+        current_level: u32,
+    ) -> Result<Box<dyn Screen>> {
+        Self::show_loading(wrapper, wrapper.run_seed)?;
+        match wrapper.get_level(current_level).await {
+            Ok(level) => {
+                if wrapper.reached_level < Some(current_level) {
+                    wrapper.reached_level = Some(current_level);
+                    wrapper.save();
+                }
+                //turning the ghost on only shows it from the next try on, so nothing is loaded while it is off
+                let best = if wrapper.options.ghost {
+                    Replay::load_best(wrapper.run_seed, current_level)
+                } else {
+                    None
+                };
+                //a ghost recorded with other movement would drift off its path
+                let best = best.filter(|best| best.tuning_hash == wrapper.tuning.hash());
+                let ghost = match best {
+                    Some(best) => {
+                        let mut ghost = Game::new(wrapper, current_level, level.clone()).await?;
+                        ghost.playback = Some(best);
+                        Some(Box::new(ghost))
+                    }
+                    None => None,
+                };
+                let mut game = Game::new(wrapper, current_level, level).await?;
+                game.ghost = ghost;
+                Ok(Box::new(game))
+            }
+            Err(error) => Ok(Box::new(ErrorScreen::new(
+                format!("Level {} can't be played:\n{}", current_level, error),
+                current_level,
+            ))),
+        }
+    }
+
+    pub(crate) async fn watch(
+        wrapper: &mut Wrapper<'_>,
+        replay: Replay,
+    ) -> Result<Box<dyn Screen>> {
+        if replay.tuning_hash != wrapper.tuning.hash() {
+            return Ok(Box::new(ErrorScreen::new(
+                format!(
+                    "The replay of level {} can't be played:\nit was recorded with different movement tuning",
+                    replay.level
+                ),
+                replay.level,
+            )));
+        }
+        Self::show_loading(wrapper, replay.run_seed)?;
+        match wrapper
+            .get_level_of_run(replay.run_seed, replay.level)
+            .await
+        {
+            Ok(level) => {
+                let mut game = Game::new(wrapper, replay.level, level).await?;
+                game.playback = Some(replay);
+                Ok(Box::new(game))
+            }
+            Err(error) => Ok(Box::new(ErrorScreen::new(
+                format!(
+                    "The replay of level {} can't be played:\n{}",
+                    replay.level, error
+                ),
+                replay.level,
+            ))),
+        }
+    }
+    fn show_loading(wrapper: &mut Wrapper<'_>, run_seed: u64) -> Result<()> {
+        let image = loading_screen(&wrapper.gfx);
+        wrapper.gfx.set_transform(Transform::IDENTITY);
+        wrapper
+            .gfx
+            .draw_image(&image, Rectangle::new((0, 0), (640, 640)));
+        wrapper.draw_text("LOADING!", Vector::new(250, 250))?;
+        let seed = format!("Seed {}", run_seed);
+        wrapper.draw_small_text(&seed, Vector::new(10, 610))?;
+        wrapper.gfx.present(&wrapper.window)
+    }
+
+    async fn new(wrapper: &mut Wrapper<'_>, current_level: u32, level: Level) -> Result<Self> {
+        let tuning = wrapper.tuning;
+        let mut mechanical_world = DefaultMechanicalWorld::new(V2::new(0.0, tuning.gravity));
+        mechanical_world.set_timestep(1. / UPDATES_PER_SECOND);
+        let geometrical_world = DefaultGeometricalWorld::new();
+
+        let mut bodies = DefaultBodySet::new();
+        let mut colliders = DefaultColliderSet::new();
+        let joint_constraints = DefaultJointConstraintSet::new();
+        let force_generators = DefaultForceGeneratorSet::new();
+
+        let mut level_as_colliders = Vec::new();
+        let y_size = level.heaven.len();
+        let mut x_size = None;
+        let blocks = World::ALL.iter().flat_map(|world| {
+            level
+                .layer(*world)
+                .iter()
+                .enumerate()
+                .map(move |(y, line)| (*world, y, line))
+        });
+        for (world, y, line) in blocks {
+            x_size = Some(line.len());
+            for (x, block) in line.iter().enumerate() {
+                if block.is_colideable() {
+                    wrapper
+                        .get_block(*block, (x * BLOCK_SIZE) as f64, (y * BLOCK_SIZE) as f64)
+                        .await;
+                    let body = RigidBodyDesc::new()
+                        .translation(V2::new((x * BLOCK_SIZE) as f64, (y * BLOCK_SIZE) as f64))
+                        .status(BodyStatus::Static)
+                        .gravity_enabled(false)
+                        .build();
+                    let reference = bodies.insert(body);
+                    let block_handler =
+                        ColliderDesc::new(ShapeHandle::new(ncollide2d::shape::Cuboid::new(
+                            V2::new(BLOCK_SIZE_I32 as f64 / 2., BLOCK_SIZE_I32 as f64 / 2.),
+                        )))
+                        .user_data(*block)
+                        .collision_groups(
+                            CollisionGroups::new().with_membership(&[collision_group(world)]),
+                        )
+                        .build(BodyPartHandle(reference, 0));
+                    let collider_handle = colliders.insert(block_handler);
+                    level_as_colliders.push((world, collider_handle));
+                }
+            }
+        }
+
+        let world = level.start_world();
+        let player_pos = level
+            .layer(world)
+            .iter()
+            .enumerate()
+            .flat_map(|(y, v)| v.iter().enumerate().map(move |(x, v)| (y, x, v)))
+            .find(|(_, _, v)| **v == Block::PlayerStart)
+            .map(|(y, x, _)| Vector::new((x * BLOCK_SIZE) as i32, (y * BLOCK_SIZE) as i32))
+            .expect("levels are checked for a start when they get loaded");
+
+        let mut player_body = RigidBodyDesc::new()
+            .translation(V2::new(player_pos.x as f64, player_pos.y as f64))
+            .gravity_enabled(true)
+            .status(BodyStatus::Dynamic)
+            .mass(1.)
+            .build();
+
+        player_body.disable_all_rotations();
+        let reference = bodies.insert(player_body);
+        let player_shape = ColliderDesc::new(ShapeHandle::new(ncollide2d::shape::Cuboid::new(
+            V2::new(tuning.width / 2., tuning.height / 2.),
+        )))
+        .collision_groups(player_collision_groups(world))
+        .ccd_enabled(true)
+        //walking speeds up and slows down on its own, so rubbing against blocks shouldn't add to that
+        .material(MaterialHandle::new(BasicMaterial {
+            friction_combine_mode: MaterialCombineMode::Min,
+            ..BasicMaterial::new(0., 0.)
+        }))
+        .density(tuning.density)
+        .build(BodyPartHandle(reference, 0));
+        let collider_handle = colliders.insert(player_shape);
+        let heaven_blocks = level_as_colliders
+            .iter()
+            .filter(|(world, _)| *world == World::Heaven)
+            .count();
+        let stars = stars(
+            wrapper.level_seed,
+            heaven_blocks * 2,
+            x_size.expect("X had no size"),
+            y_size,
+        );
+
+        let mut game = Self {
+            player_pos,
+            momentum: Vector::new(0, 0),
+            jump: JumpController::default(),
+            tuning,
+            mechanical_world,
+            previous_physics_pos: V2::new(player_pos.x as f64, player_pos.y as f64),
+            geometrical_world,
+            bodies,
+            colliders,
+            joint_constraints,
+            force_generators,
+            player_body: collider_handle,
+            level_as_colliders,
+            jump_count: 0,
+            //jumping is allowed as long as jump_count <= max_jumps
+            max_jumps: wrapper.difficulty.at(current_level).jumps - 1,
+            is_flying: false,
+            contacts: Contacts::default(),
+            gravity_inverted: false,
+            can_flip: true,
+            camera_lean: 0.,
+            render_going_to_left: false,
+            current_level,
+            stars,
+            level_size: Vector::new(x_size.unwrap() as i32, y_size as i32),
+            level,
+            world,
+            in_portal: false,
+            hint: None,
+            updates: 0,
+            queued: TickInput::default(),
+            recorded: Vec::new(),
+            playback: None,
+            ghost: None,
+        };
+        game.camera_lean = game.target_camera_lean();
+        Ok(game)
+    }
+}
+
+impl Game {
+    fn player_cell(&self) -> Cell {
+        let block = BLOCK_SIZE as f32;
+        (
+            (self.player_pos.x / block).round().max(0.) as usize,
+            (self.player_pos.y / block).round().max(0.) as usize,
+        )
+    }
+    fn overlapped_cells(&self) -> Vec<Cell> {
+        let pos = match self.colliders.get(self.player_body) {
+            Some(player) => player.position().translation.vector,
+            None => return Vec::new(),
+        };
+        let half = V2::new(self.tuning.width / 2., self.tuning.height / 2.);
+        //blocks are centered on their position, so they reach half a block to every side of it
+        let block = BLOCK_SIZE as f64;
+        let cells = |center: f64, half: f64| {
+            let first = ((center - half) / block + 0.5).floor().max(0.) as usize;
+            let last = ((center + half) / block + 0.5).floor().max(0.) as usize;
+            first..=last
+        };
+        let xs = cells(pos.x, half.x);
+        cells(pos.y, half.y)
+            .flat_map(|y| xs.clone().map(move |x| (x, y)))
+            .collect()
+    }
+    fn player_rigid_body_mut(&mut self) -> Option<&mut RigidBody<f64>> {
+        let body = self.colliders.get(self.player_body)?.body();
+        self.bodies.rigid_body_mut(body)
+    }
+    fn move_drawn_player(&mut self, interpolation: f32) -> Option<Vector> {
+        let current = self
+            .colliders
+            .get(self.player_body)?
+            .position()
+            .translation
+            .vector;
+        let pos = self.previous_physics_pos
+            + (current - self.previous_physics_pos) * f64::from(interpolation);
+        let pos = Vector::new(
+            ((pos.x as f32 + 8.) / 2.).floor() * 2.,
+            ((pos.y as f32) / 2.).floor() * 2.,
+        );
+        self.render_going_to_left = self.player_pos.x > pos.x;
+        self.player_pos = pos;
+        Some(pos)
+    }
+    fn draw_player(
+        &self,
+        wrapper: &mut Wrapper<'_>,
+        transform: Transform,
+        pos: Vector,
+        tint: Color,
+    ) {
+        let rect = Rectangle::new(pos, (PLAYER_WIDTH, PLAYER_HEIGHT));
+        let image = wrapper.get_player(!self.is_flying, self.render_going_to_left);
+        if self.gravity_inverted {
+            //the sprite gets mirrored around its own center so it stands on the ceiling
+            let center = rect.pos + rect.size / 2.;
+            wrapper.gfx.set_transform(
+                transform
+                    * Transform::translate(center)
+                    * Transform::scale(Vector::new(1., -1.))
+                    * Transform::translate(-center),
+            );
+            wrapper.gfx.draw_image_tinted(&image, rect, tint);
+            wrapper.gfx.set_transform(transform);
+        } else {
+            wrapper.gfx.draw_image_tinted(&image, rect, tint);
+        }
+    }
+    fn draw_timer(&self, wrapper: &mut Wrapper<'_>) -> Result<()> {
+        let best = wrapper
+            .records
+            .best_times
+            .get(&self.current_level)
+            .map(|best| format_time(*best))
+            .unwrap_or_else(|| String::from("-"));
+        let lines = [
+            format!("Level {}", format_time(self.updates)),
+            format!("Best {}", best),
+            format!("Run {}", format_time(wrapper.run_timer.total)),
+        ];
+        for (index, line) in lines.iter().enumerate() {
+            wrapper.draw_small_text(line, Vector::new(470, 30 + index as i32 * 25))?;
+        }
+        Ok(())
+    }
+    async fn act(
+        &mut self,
+        wrapper: &mut Wrapper<'_>,
+        action: Action,
+        is_down: bool,
+    ) -> Result<Option<Transition>> {
+        if self.playback.is_some() {
+            //everything the player does comes from the replay, so the only thing left is to stop watching
+            return Ok(Some(Transition::Pop).filter(|_| action == Action::Pause && is_down));
+        }
+        match (action, is_down) {
+            (Action::Pause, true) => {
+                return Ok(Some(Transition::Push(Box::new(Pause::new(
+                    wrapper,
+                    self.current_level,
+                )))));
+            }
+            (Action::Restart, true) => {
+                return Ok(Some(Transition::Replace(
+                    Game::load(wrapper, self.current_level).await?,
+                )));
+            }
+            //the jump itself happens in the next update, which also takes care of buffering it
+            (Action::Jump, true) => self.queued.jump_pressed = true,
+            (Action::Jump, false) => {
+                let still_held = wrapper.bindings.is_held(Action::Jump, &wrapper.held_keys)
+                    || wrapper.gamepads.is_held(Action::Jump);
+                if !still_held {
+                    self.queued.jump_released = true;
+                }
+            }
+            (Action::Flip, true) => self.queued.flip = true,
+            (Action::Swap, true) => self.queued.swap = true,
+            (Action::Hint, true) => {
+                self.hint = match self.hint {
+                    Some(_) => None,
+                    None => Some(
+                        solver::solve_from(
+                            self.level.layer(self.world),
+                            self.player_cell(),
+                            jump_envelope(&self.tuning, self.max_jumps + 1),
+                        )
+                        .map(|solution| solution.path()),
+                    ),
+                };
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+    fn apply(&mut self, input: TickInput) {
+        if input.let_go {
+            self.jump.reset();
+        }
+        if input.jump_pressed {
+            self.jump.press(&self.tuning);
+        }
+        if input.jump_released {
+            self.jump.release();
+        }
+        if input.flip && self.can_flip {
+            self.can_flip = false;
+            self.flip_gravity();
+        }
+        if input.swap {
+            self.swap_world();
+        }
+    }
+    fn try_jump(&mut self) {
+        let contacts = self.contacts;
+        let tuning = self.tuning;
+        let (kick, speed, jump_count) = if self.jump.can_jump_off_floor(&tuning) {
+            (None, tuning.jump_speed, 1)
+        } else if contacts.touches_wall() {
+            //jumping off a wall works like jumping off the floor, so air jumps are back too
+            let away = if contacts.left_wall { 1. } else { -1. };
+            (
+                Some(tuning.wall_jump_kick * away),
+                tuning.wall_jump_speed,
+                1,
+            )
+        } else if self.max_jumps >= self.jump_count {
+            (None, tuning.jump_speed, self.jump_count + 1)
+        } else {
+            return;
+        };
+        let down = self.gravity_direction();
+        if let Some(body) = self.player_rigid_body_mut() {
+            let current = body.velocity().linear;
+            body.set_linear_velocity(V2::new(kick.unwrap_or(current.x), -speed * down));
+            self.jump_count = jump_count;
+            self.jump.jumped();
+        }
+    }
+    fn find_contacts(&self) -> Contacts {
+        let mut contacts = Contacts::default();
+        let down = self.gravity_direction();
+        let pairs = self
+            .geometrical_world
+            .contacts_with(&self.colliders, self.player_body, true);
+        for (first, _, _, _, _, manifold) in pairs.into_iter().flatten() {
+            //normals point from the first collider to the second one
+            let away = if first == self.player_body { 1. } else { -1. };
+            for contact in manifold.contacts() {
+                contacts.add(
+                    contact.contact.normal.into_inner() * away,
+                    contact.contact.depth,
+                    down,
+                );
+            }
+        }
+        contacts
+    }
+    fn touches_portal(&self) -> bool {
+        let pairs = self
+            .geometrical_world
+            .contacts_with(&self.colliders, self.player_body, true);
+        pairs
+            .into_iter()
+            .flatten()
+            .map(|(first, _, second, _, _, _)| {
+                if first == self.player_body {
+                    second
+                } else {
+                    first
+                }
+            })
+            .filter_map(|other| self.colliders.get(other))
+            .filter_map(|collider| collider.user_data())
+            .any(|block| block.downcast_ref::<Block>() == Some(&Block::Portal))
+    }
+    fn swap_world(&mut self) {
+        let other = self.world.other();
+        let layer = self.level.layer(other);
+        if !self
+            .overlapped_cells()
+            .into_iter()
+            .all(|cell| movement::is_free(layer, cell))
+        {
+            return;
+        }
+        self.world = other;
+        self.in_portal = true;
+        self.hint = None;
+        if let Some(player) = self.colliders.get_mut(self.player_body) {
+            player.set_collision_groups(player_collision_groups(other));
+        }
+    }
+    fn falling_speed(&self) -> f64 {
+        self.colliders
+            .get(self.player_body)
+            .and_then(|player| self.bodies.rigid_body(player.body()))
+            .map(|body| body.velocity().linear.y * self.gravity_direction())
+            .unwrap_or(0.)
+    }
+    //the camera only looks ahead when the player is headed somewhere it can't see yet
+    fn target_camera_lean(&self) -> f32 {
+        let falling = !self.contacts.floor && self.falling_speed() > CAMERA_LEAN_FALLING_SPEED;
+        if self.gravity_inverted || falling {
+            CAMERA_LEAN * self.gravity_direction() as f32
+        } else {
+            0.
+        }
+    }
+    fn gravity_direction(&self) -> f64 {
+        if self.gravity_inverted {
+            -1.
+        } else {
+            1.
+        }
+    }
+    fn flip_gravity(&mut self) {
+        self.gravity_inverted = !self.gravity_inverted;
+        self.mechanical_world.gravity = V2::new(0., self.tuning.gravity * self.gravity_direction());
+    }
+}
+
+const BLOCK_SIZE_I32: i32 = 32;
+
+fn stars(level_seed: u64, count: usize, width: usize, height: usize) -> Vec<Rectangle> {
+    let mut rng = seeds::rng(seeds::derive(level_seed, seeds::STARS));
+    (0..count)
+        .map(|_| {
+            Rectangle::new(
+                (
+                    rng.gen_range(0, (width as i32 * BLOCK_SIZE_I32 + 1) / 2) * 2,
+                    (rng.gen_range(0, height as i32 * BLOCK_SIZE_I32 + 1) / 2) * 2,
+                ),
+                (2, 2),
+            )
+        })
+        .collect()
+}
+
+fn collision_group(world: World) -> usize {
+    match world {
+        World::Heaven => 0,
+        World::Hell => 1,
+    }
+}
+
+fn player_collision_groups(world: World) -> CollisionGroups {
+    CollisionGroups::new()
+        .with_membership(&[PLAYER_COLLISION_GROUP])
+        .with_whitelist(&[collision_group(world)])
+}
+
+#[async_trait(?Send)]
+impl Screen for Game {
+    async fn draw(&mut self, wrapper: &mut crate::Wrapper<'_>) -> quicksilver::Result<()> {
+        if let Some(pos) = self.move_drawn_player(wrapper.interpolation) {
+            let cam_pos = {
+                let mut cam_pos = Vector::new(self.player_pos.x - 320., self.player_pos.y - 320.);
+                cam_pos.x += 8.;
+                cam_pos.y += PLAYER_HEIGHT as f32 / 2. + self.camera_lean;
+                if cam_pos.x < 0. {
+                    cam_pos.x = 0.;
+                }
+                if cam_pos.y < 0. {
+                    cam_pos.y = 0.;
+                }
+                if cam_pos.y > (self.level_size.y * BLOCK_SIZE_I32 as f32) - 640. {
+                    cam_pos.y = (self.level_size.y * BLOCK_SIZE_I32 as f32) - 640.
+                }
+                if cam_pos.x > (self.level_size.x * BLOCK_SIZE_I32 as f32) - 640. {
+                    cam_pos.x = (self.level_size.x * BLOCK_SIZE_I32 as f32) - 640.
+                }
+
+                cam_pos.x = cam_pos.x.floor();
+                cam_pos.y = cam_pos.y.floor();
+                cam_pos
+            };
+            let transform = Transform::translate(cam_pos).inverse();
+            wrapper.gfx.set_transform(transform);
+            wrapper.gfx.clear(match self.world {
+                World::Heaven => Color::BLACK,
+                World::Hell => Color::from_rgba(50, 5, 5, 1.),
+            });
+            for star in &self.stars {
+                wrapper.gfx.fill_rect(star, Color::WHITE);
+            }
+            //the other world is drawn faded out first, so the player can see what swapping would do
+            let mut blocks = self.level_as_colliders.clone();
+            blocks.sort_by_key(|(world, _)| *world == self.world);
+            for (world, collider) in blocks {
+                if let Some(collider) = self.colliders.get(collider) {
+                    let pos = collider.position().translation;
+                    let rec = Rectangle::new(
+                        (pos.x as f32, pos.y as f32),
+                        (BLOCK_SIZE_I32, BLOCK_SIZE_I32),
+                    );
+                    let block = wrapper
+                        .get_block(
+                            collider
+                                .user_data()
+                                .and_then(|v| v.downcast_ref::<Block>().copied())
+                                .unwrap_or(Block::Dirt),
+                            pos.x,
+                            pos.y,
+                        )
+                        .await;
+                    if world == self.world {
+                        wrapper.gfx.draw_image(&block, rec)
+                    } else {
+                        wrapper
+                            .gfx
+                            .draw_image_tinted(&block, rec, Color::WHITE.with_alpha(0.2))
+                    }
+                }
+            }
+            if let Some(Ok(hint)) = &self.hint {
+                for (x, y) in hint {
+                    let center = Vector::new(
+                        (*x * BLOCK_SIZE + BLOCK_SIZE / 2) as i32,
+                        (*y * BLOCK_SIZE + BLOCK_SIZE / 2) as i32,
+                    );
+                    wrapper.gfx.fill_rect(
+                        &Rectangle::new(center - Vector::new(4, 4), (8, 8)),
+                        Color::YELLOW.with_alpha(0.6),
+                    );
+                }
+            }
+            if let Some(ghost) = self.ghost.as_mut().filter(|_| wrapper.options.ghost) {
+                if let Some(ghost_pos) = ghost.move_drawn_player(wrapper.interpolation) {
+                    ghost.draw_player(wrapper, transform, ghost_pos, Color::WHITE.with_alpha(0.4));
+                }
+            }
+            self.draw_player(wrapper, transform, pos, Color::WHITE);
+            wrapper.gfx.set_transform(Transform::IDENTITY);
+            if let Some(Err(unsolvable)) = &self.hint {
+                wrapper
+                    .draw_small_text(&format!("No hint: {}", unsolvable), Vector::new(10, 30))?;
+            }
+            if wrapper.options.speedrun {
+                self.draw_timer(wrapper)?;
+            }
+            if self.playback.is_some() {
+                let text = format!(
+                    "Watching a replay, {} stops it.",
+                    key_names(&wrapper.bindings, Action::Pause)
+                );
+                wrapper.draw_small_text(&text, Vector::new(10, 610))?;
+            }
+        }
+        Ok(())
+    }
+    async fn update(
+        &mut self,
+        wrapper: &mut crate::Wrapper<'_>,
+    ) -> quicksilver::Result<Option<Transition>> {
+        if self.tuning != wrapper.tuning {
+            self.tuning = wrapper.tuning;
+            //the weight of the player only changes once the level gets loaded again
+            if let Some(player) = self.colliders.get_mut(self.player_body) {
+                player.set_shape(ShapeHandle::new(ncollide2d::shape::Cuboid::new(V2::new(
+                    self.tuning.width / 2.,
+                    self.tuning.height / 2.,
+                ))));
+            }
+            self.mechanical_world.gravity =
+                V2::new(0., self.tuning.gravity * self.gravity_direction());
+        }
+        if let Some(ghost) = &mut self.ghost {
+            //the ghost disappears once its run is over
+            if ghost.update(wrapper).await?.is_some() {
+                self.ghost = None;
+            }
+        }
+        self.updates += 1;
+        if self.playback.is_none() {
+            wrapper.run_timer.total += 1;
+        }
+        if let Some(player) = self.colliders.get(self.player_body) {
+            self.previous_physics_pos = player.position().translation.vector;
+        }
+        let bindings = &wrapper.bindings;
+        let gamepads = &wrapper.gamepads;
+        let held_keys = &wrapper.held_keys;
+        let held = |action| {
+            if bindings.is_held(action, held_keys) || gamepads.is_held(action) {
+                1.
+            } else {
+                0.
+            }
+        };
+        //the stick adds to the keys, so either can be used without letting go of the other
+        let stick = f64::from(gamepads.axis(GamepadAxis::LeftStickX));
+        let direction = held(Action::MoveRight) - held(Action::MoveLeft) + stick;
+        let mut input = std::mem::take(&mut self.queued);
+        input.set_direction(direction);
+        if let Some(replay) = &self.playback {
+            match replay.inputs.get(self.recorded.len()) {
+                Some(recorded) => input = *recorded,
+                None => return Ok(Some(Transition::Pop)),
+            }
+        }
+        self.recorded.push(input);
+        self.apply(input);
+        let tuning = self.tuning;
+        let on_floor = self.contacts.floor;
+        if let Some(body) = self.player_rigid_body_mut() {
+            let velocity = body.velocity().linear;
+            let speed = tuning.walk(
+                velocity.x,
+                input.direction(),
+                on_floor,
+                1. / UPDATES_PER_SECOND,
+            );
+            body.set_linear_velocity(V2::new(speed, velocity.y));
+        }
+        if self.jump.wants_jump() {
+            self.try_jump();
+        }
+        if self.jump.take_release() {
+            let down = self.gravity_direction();
+            let cut_short = self.tuning.cut_short;
+            if let Some(body) = self.player_rigid_body_mut() {
+                let velocity = body.velocity().linear;
+                //letting go only matters while the jump still goes up
+                if velocity.y * down < 0. {
+                    body.set_linear_velocity(V2::new(velocity.x, velocity.y * cut_short));
+                }
+            }
+        }
+        self.camera_lean += (self.target_camera_lean() - self.camera_lean) * CAMERA_LEAN_SPEED;
+        self.mechanical_world.step(
+            &mut self.geometrical_world,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.joint_constraints,
+            &mut self.force_generators,
+        );
+
+        let mut touched_flip = false;
+        let mut touched_portal = false;
+        for contact in self.geometrical_world.contact_events() {
+            match contact {
+                ncollide2d::pipeline::ContactEvent::Started(x, y) => {
+                    let other = if x == &self.player_body {
+                        Some(y)
+                    } else if y == &self.player_body {
+                        Some(x)
+                    } else {
+                        None
+                    };
+                    let touched = other
+                        .and_then(|other| self.colliders.get(*other))
+                        .and_then(|collider| collider.user_data())
+                        .and_then(|block| block.downcast_ref::<Block>().copied());
+                    let is_end = touched == Some(Block::PlayerEnd);
+                    if self.playback.is_some() && (is_end || touched == Some(Block::Spikes)) {
+                        return Ok(Some(Transition::Pop));
+                    }
+                    if is_end {
+                        let split = Split {
+                            level: self.current_level,
+                            updates: self.updates,
+                            best_before: wrapper
+                                .records
+                                .best_times
+                                .get(&self.current_level)
+                                .copied(),
+                        };
+                        let is_best = wrapper.records.finished(self.current_level, self.updates);
+                        wrapper.run_timer.splits.push(split);
+                        wrapper.save();
+                        let replay = Replay {
+                            run_seed: wrapper.run_seed,
+                            level: self.current_level,
+                            tuning_hash: self.tuning.hash(),
+                            inputs: std::mem::take(&mut self.recorded),
+                        };
+                        replay.save();
+                        if is_best {
+                            replay.save_best();
+                        }
+                        return Ok(Some(Transition::Replace(Box::new(Results::new(
+                            wrapper, split, replay,
+                        )))));
+                    }
+                    if touched == Some(Block::Spikes) {
+                        wrapper.records.died(self.current_level);
+                        wrapper.save();
+                        return Ok(Some(Transition::Replace(
+                            Game::load(wrapper, self.current_level).await?,
+                        )));
+                    }
+                    match touched {
+                        Some(Block::Flip) => touched_flip = true,
+                        Some(Block::Portal) => touched_portal = true,
+                        _ => {}
+                    }
+                }
+                ncollide2d::pipeline::ContactEvent::Stopped(_, _) => {}
+            }
+        }
+        self.contacts = self.find_contacts();
+        let falling_speed = self.falling_speed();
+        //right after a jump the floor is still touched for a moment, which shouldn't give the jump back
+        let standing = self.contacts.floor && falling_speed >= 0.;
+        self.jump.tick(standing);
+        if standing {
+            self.jump_count = 0;
+            self.can_flip = true;
+        } else if self.jump.missed_floor_jump(&self.tuning) && self.jump_count == 0 {
+            self.jump_count = 1;
+        }
+        self.is_flying = !self.contacts.floor;
+        if !self.contacts.floor
+            && self.contacts.touches_wall()
+            && falling_speed > self.tuning.wall_slide_speed
+        {
+            let down = self.gravity_direction();
+            let wall_slide_speed = self.tuning.wall_slide_speed;
+            if let Some(body) = self.player_rigid_body_mut() {
+                let velocity = body.velocity().linear;
+                body.set_linear_velocity(V2::new(velocity.x, wall_slide_speed * down));
+            }
+        }
+        if touched_flip {
+            self.flip_gravity();
+        }
+        if self.in_portal && !self.touches_portal() {
+            self.in_portal = false;
+        }
+        if touched_portal && !self.in_portal {
+            self.swap_world();
+        }
+        self.player_pos += self.momentum;
+
+        Ok(None)
+    }
+    async fn event(
+        &mut self,
+        wrapper: &mut Wrapper<'_>,
+        event: &Event,
+    ) -> Result<Option<Transition>> {
+        let (key, is_down) = match event {
+            Event::KeyboardInput(x) => (x.key(), x.is_down()),
+            _ => return Ok(None),
+        };
+        match wrapper.bindings.action_for(key) {
+            Some(action) => self.act(wrapper, action, is_down).await,
+            None => Ok(None),
+        }
+    }
+    async fn pad_event(
+        &mut self,
+        wrapper: &mut Wrapper<'_>,
+        event: &PadEvent,
+        _step: Option<MenuStep>,
+    ) -> Result<Option<Transition>> {
+        match event.action() {
+            Some((action, is_down)) => self.act(wrapper, action, is_down).await,
+            None => Ok(None),
+        }
+    }
+    fn set_covered(&mut self, _covered: bool) {
+        //keys let go of while something else was on top never reach the game, which would leave a jump held
+        self.queued = TickInput {
+            let_go: true,
+            ..TickInput::default()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_stars() {
+        let positions = |seed| -> Vec<Vector> {
+            stars(seed, 40, 11, 11)
+                .iter()
+                .map(|star| star.pos)
+                .collect()
+        };
+        assert_eq!(positions(7), positions(7));
+        assert_ne!(positions(7), positions(8));
+    }
+}
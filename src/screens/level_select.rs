@@ -0,0 +1,104 @@
+use crate::{
+    gamepad::{MenuStep, PadEvent},
+    screens::{add_button, game::Game, menu_row, MenuNavigation},
+    timer::RunTimer,
+    Screen, Transition, Wrapper,
+};
+use mergui::{
+    channels::{BasicClickable, Clickable},
+    LayerId, Response,
+};
+use quicksilver::{
+    geom::{Rectangle, Transform, Vector},
+    graphics::Color,
+    Result,
+};
+
+use async_trait::async_trait;
+
+const COLUMNS: u32 = 6;
+const ROWS: u32 = 5;
+
+pub struct LevelSelect {
+    _layer: LayerId,
+    levels: Vec<(u32, Rectangle, Response<BasicClickable>)>,
+    back: Response<BasicClickable>,
+    menu: MenuNavigation,
+}
+
+impl LevelSelect {
+    pub(crate) fn new(wrapper: &mut Wrapper<'_>) -> Self {
+        let layer = wrapper.context.add_layer();
+        let reached = wrapper.reached_level.unwrap_or(1);
+        //only the last levels fit on the screen, which are the interesting ones anyway
+        let first = reached.saturating_sub(COLUMNS * ROWS - 1).max(1);
+        let levels: Vec<_> = (first..=reached)
+            .map(|level| {
+                let index = level - first;
+                let location = Rectangle::new(
+                    (
+                        50 + (index % COLUMNS) as i32 * 95,
+                        100 + (index / COLUMNS) as i32 * 70,
+                    ),
+                    (80, 50),
+                );
+                let button = add_button(wrapper, &layer, &level.to_string(), location);
+                (level, location, button)
+            })
+            .collect();
+        let back = add_button(wrapper, &layer, "Back", menu_row(5));
+        let menu = MenuNavigation::new(
+            levels
+                .iter()
+                .map(|(_, location, _)| *location)
+                .chain(Some(menu_row(5)))
+                .collect(),
+        );
+        Self {
+            _layer: layer,
+            levels,
+            back,
+            menu,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Screen for LevelSelect {
+    async fn draw(&mut self, wrapper: &mut Wrapper<'_>) -> Result<()> {
+        wrapper.gfx.set_transform(Transform::IDENTITY);
+        wrapper.gfx.clear(Color::BLACK);
+        self.menu.draw(wrapper);
+        wrapper.draw_small_text("Pick a level", Vector::new(50, 60))
+    }
+    async fn update(&mut self, wrapper: &mut Wrapper<'_>) -> Result<Option<Transition>> {
+        let menu = &mut self.menu;
+        let picked = self
+            .levels
+            .iter_mut()
+            .find_map(|(level, location, button)| {
+                Some(*level).filter(|_| button.channel.has_clicked() || menu.pressed(*location))
+            });
+        if let Some(level) = picked {
+            wrapper.run_timer = RunTimer::default();
+            return Ok(Some(Transition::ClearTo(Game::load(wrapper, level).await?)));
+        }
+        if self.back.channel.has_clicked() || self.menu.pressed(menu_row(5)) {
+            return Ok(Some(Transition::Pop));
+        }
+        Ok(None)
+    }
+    async fn pad_event(
+        &mut self,
+        _wrapper: &mut Wrapper<'_>,
+        _event: &PadEvent,
+        step: Option<MenuStep>,
+    ) -> Result<Option<Transition>> {
+        if let Some(step) = step {
+            if self.menu.step(step) {
+                return Ok(Some(Transition::Pop));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -1 +1,104 @@
-pub mod menu;
+use crate::{gamepad::MenuStep, Wrapper};
+use mergui::{channels::BasicClickable, widgets::ButtonConfig, FontStyle, LayerId, Response};
+use quicksilver::{
+    geom::{Rectangle, Vector},
+    graphics::Color,
+};
+
+pub mod controls;
+pub mod error;
+pub mod game;
+pub mod level_select;
+pub mod options;
+pub mod pause;
+pub mod results;
+pub mod stack;
+pub mod title;
+
+pub(crate) fn menu_row(row: usize) -> Rectangle {
+    Rectangle::new((195, 200 + row as i32 * 70), (250, 50))
+}
+
+pub(crate) fn add_button(
+    wrapper: &mut Wrapper<'_>,
+    layer: &LayerId,
+    text: &str,
+    location: Rectangle,
+) -> Response<BasicClickable> {
+    let config = ButtonConfig {
+        text: text.to_string(),
+        font_style: FontStyle {
+            font: wrapper.button_font.clone(),
+            location: Vector::new(15, 35),
+            color: Color::WHITE,
+        },
+        background: wrapper.button_background.clone(),
+        background_location: location,
+        blend_color: Some(Color::from_rgba(40, 40, 40, 0.9)),
+        hover_color: Some(Color::from_rgba(90, 90, 90, 0.9)),
+    };
+    wrapper
+        .context
+        .add_widget(config, layer)
+        .expect("buttons are only added to layers owned by the screen adding them")
+}
+
+pub(crate) fn relabel_button(
+    button: &mut Response<BasicClickable>,
+    wrapper: &mut Wrapper<'_>,
+    layer: &LayerId,
+    text: &str,
+    location: Rectangle,
+) {
+    //mergui can't change the text of a button, so it gets replaced by a new one instead
+    *button = add_button(wrapper, layer, text, location);
+}
+
+pub(crate) struct MenuNavigation {
+    buttons: Vec<Rectangle>,
+    selected: Option<usize>,
+    confirmed: bool,
+}
+
+impl MenuNavigation {
+    pub(crate) fn new(buttons: Vec<Rectangle>) -> Self {
+        Self {
+            buttons,
+            selected: None,
+            confirmed: false,
+        }
+    }
+    pub(crate) fn step(&mut self, step: MenuStep) -> bool {
+        let count = self.buttons.len();
+        if count == 0 {
+            return step == MenuStep::Back;
+        }
+        self.confirmed = false;
+        match (step, self.selected) {
+            (MenuStep::Back, _) => return true,
+            (MenuStep::Confirm, Some(_)) => self.confirmed = true,
+            (MenuStep::Confirm, None) | (MenuStep::Next, None) => self.selected = Some(0),
+            (MenuStep::Previous, None) => self.selected = Some(count - 1),
+            (MenuStep::Next, Some(at)) => self.selected = Some((at + 1) % count),
+            (MenuStep::Previous, Some(at)) => self.selected = Some((at + count - 1) % count),
+        }
+        false
+    }
+    pub(crate) fn pressed(&mut self, button: Rectangle) -> bool {
+        let pressed = self.confirmed && self.selected.map(|at| self.buttons[at]) == Some(button);
+        if pressed {
+            self.confirmed = false;
+        }
+        pressed
+    }
+    pub(crate) fn draw(&self, wrapper: &mut Wrapper<'_>) {
+        if let Some(at) = self.selected {
+            let button = self.buttons[at];
+            let border = Vector::new(4, 4);
+            wrapper.gfx.fill_rect(
+                &Rectangle::new(button.pos - border, button.size + border * 2.),
+                Color::from_rgba(230, 190, 60, 1.),
+            );
+        }
+    }
+}
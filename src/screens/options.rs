@@ -0,0 +1,131 @@
+use crate::{
+    gamepad::{MenuStep, PadEvent},
+    screens::{add_button, controls::Controls, menu_row, relabel_button, MenuNavigation},
+    Screen, Transition, Wrapper,
+};
+use mergui::{
+    channels::{BasicClickable, Clickable},
+    LayerId, Response,
+};
+use quicksilver::{
+    geom::{Transform, Vector},
+    graphics::Color,
+    Result,
+};
+
+use async_trait::async_trait;
+
+pub struct OptionsScreen {
+    layer: LayerId,
+    fullscreen: Response<BasicClickable>,
+    speedrun: Response<BasicClickable>,
+    ghost: Response<BasicClickable>,
+    controls: Response<BasicClickable>,
+    back: Response<BasicClickable>,
+    menu: MenuNavigation,
+}
+
+impl OptionsScreen {
+    pub(crate) fn new(wrapper: &mut Wrapper<'_>) -> Self {
+        let layer = wrapper.context.add_layer();
+        let fullscreen = add_button(
+            wrapper,
+            &layer,
+            &toggle_text("Fullscreen", wrapper.options.fullscreen),
+            menu_row(0),
+        );
+        let speedrun = add_button(
+            wrapper,
+            &layer,
+            &toggle_text("Timer", wrapper.options.speedrun),
+            menu_row(2),
+        );
+        let ghost = add_button(
+            wrapper,
+            &layer,
+            &toggle_text("Ghost", wrapper.options.ghost),
+            menu_row(3),
+        );
+        let controls = add_button(wrapper, &layer, "Controls", menu_row(1));
+        let back = add_button(wrapper, &layer, "Back", menu_row(5));
+        Self {
+            layer,
+            fullscreen,
+            speedrun,
+            ghost,
+            controls,
+            back,
+            menu: MenuNavigation::new(vec![
+                menu_row(0),
+                menu_row(1),
+                menu_row(2),
+                menu_row(3),
+                menu_row(5),
+            ]),
+        }
+    }
+}
+
+fn toggle_text(name: &str, on: bool) -> String {
+    format!("{}: {}", name, if on { "on" } else { "off" })
+}
+
+#[async_trait(?Send)]
+impl Screen for OptionsScreen {
+    async fn draw(&mut self, wrapper: &mut Wrapper<'_>) -> Result<()> {
+        wrapper.gfx.set_transform(Transform::IDENTITY);
+        wrapper.gfx.clear(Color::BLACK);
+        self.menu.draw(wrapper);
+        wrapper.draw_small_text("Options", Vector::new(195, 160))
+    }
+    async fn update(&mut self, wrapper: &mut Wrapper<'_>) -> Result<Option<Transition>> {
+        if self.fullscreen.channel.has_clicked() || self.menu.pressed(menu_row(0)) {
+            wrapper.options.fullscreen = !wrapper.options.fullscreen;
+            wrapper.window.set_fullscreen(wrapper.options.fullscreen);
+            wrapper.save();
+            let text = toggle_text("Fullscreen", wrapper.options.fullscreen);
+            relabel_button(
+                &mut self.fullscreen,
+                wrapper,
+                &self.layer,
+                &text,
+                menu_row(0),
+            );
+        }
+        if self.speedrun.channel.has_clicked() || self.menu.pressed(menu_row(2)) {
+            wrapper.options.speedrun = !wrapper.options.speedrun;
+            wrapper.save();
+            let text = toggle_text("Timer", wrapper.options.speedrun);
+            relabel_button(&mut self.speedrun, wrapper, &self.layer, &text, menu_row(2));
+        }
+        if self.ghost.channel.has_clicked() || self.menu.pressed(menu_row(3)) {
+            wrapper.options.ghost = !wrapper.options.ghost;
+            wrapper.save();
+            let text = toggle_text("Ghost", wrapper.options.ghost);
+            relabel_button(&mut self.ghost, wrapper, &self.layer, &text, menu_row(3));
+        }
+        if self.controls.channel.has_clicked() || self.menu.pressed(menu_row(1)) {
+            return Ok(Some(Transition::Push(Box::new(Controls::new(wrapper)))));
+        }
+        if self.back.channel.has_clicked() || self.menu.pressed(menu_row(5)) {
+            return Ok(Some(Transition::Pop));
+        }
+        Ok(None)
+    }
+    async fn pad_event(
+        &mut self,
+        _wrapper: &mut Wrapper<'_>,
+        _event: &PadEvent,
+        step: Option<MenuStep>,
+    ) -> Result<Option<Transition>> {
+        if let Some(step) = step {
+            if self.menu.step(step) {
+                return Ok(Some(Transition::Pop));
+            }
+        }
+        Ok(None)
+    }
+    fn set_covered(&mut self, covered: bool) {
+        self.layer.set_is_active(!covered);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{
+    gamepad::{MenuStep, PadEvent},
+    input::Action,
+    screens::{
+        add_button, game::Game, menu_row, options::OptionsScreen, title::Title, MenuNavigation,
+    },
+    Screen, Transition, Wrapper,
+};
+use mergui::{
+    channels::{BasicClickable, Clickable},
+    LayerId, Response,
+};
+use quicksilver::{
+    geom::{Rectangle, Transform, Vector},
+    graphics::Color,
+    lifecycle::Event,
+    Result,
+};
+
+use async_trait::async_trait;
+
+pub struct Pause {
+    layer: LayerId,
+    level: u32,
+    resume: Response<BasicClickable>,
+    restart: Response<BasicClickable>,
+    options: Response<BasicClickable>,
+    quit: Response<BasicClickable>,
+    menu: MenuNavigation,
+}
+
+impl Pause {
+    pub(crate) fn new(wrapper: &mut Wrapper<'_>, level: u32) -> Self {
+        let layer = wrapper.context.add_layer();
+        let resume = add_button(wrapper, &layer, "Resume", menu_row(0));
+        let restart = add_button(wrapper, &layer, "Restart level", menu_row(1));
+        let options = add_button(wrapper, &layer, "Options", menu_row(2));
+        let quit = add_button(wrapper, &layer, "Quit to title", menu_row(3));
+        Self {
+            layer,
+            level,
+            resume,
+            restart,
+            options,
+            quit,
+            menu: MenuNavigation::new((0..4).map(menu_row).collect()),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Screen for Pause {
+    async fn draw(&mut self, wrapper: &mut Wrapper<'_>) -> Result<()> {
+        wrapper.gfx.set_transform(Transform::IDENTITY);
+        wrapper.gfx.fill_rect(
+            &Rectangle::new((0, 0), (640, 640)),
+            Color::BLACK.with_alpha(0.6),
+        );
+        self.menu.draw(wrapper);
+        wrapper.draw_small_text("Paused", Vector::new(195, 160))
+    }
+    async fn update(&mut self, wrapper: &mut Wrapper<'_>) -> Result<Option<Transition>> {
+        if self.resume.channel.has_clicked() || self.menu.pressed(menu_row(0)) {
+            return Ok(Some(Transition::Pop));
+        }
+        if self.restart.channel.has_clicked() || self.menu.pressed(menu_row(1)) {
+            return Ok(Some(Transition::ClearTo(
+                Game::load(wrapper, self.level).await?,
+            )));
+        }
+        if self.options.channel.has_clicked() || self.menu.pressed(menu_row(2)) {
+            return Ok(Some(Transition::Push(Box::new(OptionsScreen::new(
+                wrapper,
+            )))));
+        }
+        if self.quit.channel.has_clicked() || self.menu.pressed(menu_row(3)) {
+            return Ok(Some(Transition::ClearTo(Box::new(Title::new(wrapper)))));
+        }
+        Ok(None)
+    }
+    async fn event(
+        &mut self,
+        wrapper: &mut Wrapper<'_>,
+        event: &Event,
+    ) -> Result<Option<Transition>> {
+        if let Event::KeyboardInput(x) = event {
+            if x.is_down() && wrapper.bindings.action_for(x.key()) == Some(Action::Pause) {
+                return Ok(Some(Transition::Pop));
+            }
+        }
+        Ok(None)
+    }
+    async fn pad_event(
+        &mut self,
+        _wrapper: &mut Wrapper<'_>,
+        event: &PadEvent,
+        step: Option<MenuStep>,
+    ) -> Result<Option<Transition>> {
+        if event.action() == Some((Action::Pause, true)) {
+            return Ok(Some(Transition::Pop));
+        }
+        if let Some(step) = step {
+            if self.menu.step(step) {
+                return Ok(Some(Transition::Pop));
+            }
+        }
+        Ok(None)
+    }
+    fn is_overlay(&self) -> bool {
+        true
+    }
+    fn set_covered(&mut self, covered: bool) {
+        self.layer.set_is_active(!covered);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{
+    gamepad::{MenuStep, PadEvent},
+    input::Action,
+    replay::Replay,
+    screens::{add_button, game::Game, menu_row, title::Title, MenuNavigation},
+    timer::{format_difference, format_time, Split},
+    Screen, Transition, Wrapper,
+};
+use mergui::{
+    channels::{BasicClickable, Clickable},
+    LayerId, Response,
+};
+use quicksilver::{
+    geom::{Transform, Vector},
+    graphics::Color,
+    lifecycle::Event,
+    Result,
+};
+
+use async_trait::async_trait;
+
+const SHOWN_SPLITS: usize = 6;
+
+pub struct Results {
+    layer: LayerId,
+    split: Split,
+    replay: Replay,
+    next: Response<BasicClickable>,
+    watch: Response<BasicClickable>,
+    retry: Response<BasicClickable>,
+    quit: Response<BasicClickable>,
+    menu: MenuNavigation,
+}
+
+impl Results {
+    pub(crate) fn new(wrapper: &mut Wrapper<'_>, split: Split, replay: Replay) -> Self {
+        let layer = wrapper.context.add_layer();
+        let next = add_button(wrapper, &layer, "Next level", menu_row(2));
+        let watch = add_button(wrapper, &layer, "Watch replay", menu_row(3));
+        let retry = add_button(wrapper, &layer, "Retry", menu_row(4));
+        let quit = add_button(wrapper, &layer, "Quit to title", menu_row(5));
+        Self {
+            layer,
+            split,
+            replay,
+            next,
+            watch,
+            retry,
+            quit,
+            menu: MenuNavigation::new((2..6).map(menu_row).collect()),
+        }
+    }
+    async fn next(&self, wrapper: &mut Wrapper<'_>) -> Result<Option<Transition>> {
+        Ok(Some(Transition::Replace(
+            Game::load(wrapper, self.split.level + 1).await?,
+        )))
+    }
+    async fn retry(&self, wrapper: &mut Wrapper<'_>) -> Result<Option<Transition>> {
+        Ok(Some(Transition::Replace(
+            Game::load(wrapper, self.split.level).await?,
+        )))
+    }
+}
+
+fn compared(split: &Split) -> String {
+    match split.best_before {
+        Some(best) if split.updates < best => {
+            format!("New best! ({})", format_difference(split.updates, best))
+        }
+        Some(best) => format!(
+            "Best: {} ({})",
+            format_time(best),
+            format_difference(split.updates, best)
+        ),
+        None => String::from("First time finishing it"),
+    }
+}
+
+#[async_trait(?Send)]
+impl Screen for Results {
+    async fn draw(&mut self, wrapper: &mut Wrapper<'_>) -> Result<()> {
+        wrapper.gfx.set_transform(Transform::IDENTITY);
+        wrapper.gfx.clear(Color::BLACK);
+        self.menu.draw(wrapper);
+        let split = self.split;
+        let deaths = wrapper
+            .records
+            .deaths
+            .get(&split.level)
+            .copied()
+            .unwrap_or(0);
+        let lines = [
+            format!("Level {} done", split.level),
+            format!("Time: {}", format_time(split.updates)),
+            compared(&split),
+            format!("Deaths: {}", deaths),
+            format!("Run: {}", format_time(wrapper.run_timer.total)),
+        ];
+        for (index, line) in lines.iter().enumerate() {
+            wrapper.draw_small_text(line, Vector::new(40, 40 + index as i32 * 30))?;
+        }
+        let splits = &wrapper.run_timer.splits;
+        let shown: Vec<_> = splits
+            .iter()
+            .skip(splits.len().saturating_sub(SHOWN_SPLITS))
+            .map(|split| {
+                let difference = split
+                    .best_before
+                    .map(|best| format_difference(split.updates, best))
+                    .unwrap_or_default();
+                format!(
+                    "Level {}: {} {}",
+                    split.level,
+                    format_time(split.updates),
+                    difference
+                )
+            })
+            .collect();
+        for (index, line) in shown.iter().enumerate() {
+            wrapper.draw_small_text(line, Vector::new(340, 70 + index as i32 * 30))?;
+        }
+        Ok(())
+    }
+    async fn update(&mut self, wrapper: &mut Wrapper<'_>) -> Result<Option<Transition>> {
+        if self.next.channel.has_clicked() || self.menu.pressed(menu_row(2)) {
+            return self.next(wrapper).await;
+        }
+        if self.watch.channel.has_clicked() || self.menu.pressed(menu_row(3)) {
+            return Ok(Some(Transition::Push(
+                Game::watch(wrapper, self.replay.clone()).await?,
+            )));
+        }
+        if self.retry.channel.has_clicked() || self.menu.pressed(menu_row(4)) {
+            return self.retry(wrapper).await;
+        }
+        if self.quit.channel.has_clicked() || self.menu.pressed(menu_row(5)) {
+            return Ok(Some(Transition::ClearTo(Box::new(Title::new(wrapper)))));
+        }
+        Ok(None)
+    }
+    async fn event(
+        &mut self,
+        wrapper: &mut Wrapper<'_>,
+        event: &Event,
+    ) -> Result<Option<Transition>> {
+        if let Event::KeyboardInput(x) = event {
+            if !x.is_down() {
+                return Ok(None);
+            }
+            //the keys used while playing work here too, so runs don't need the mouse
+            match wrapper.bindings.action_for(x.key()) {
+                Some(Action::Jump) => return self.next(wrapper).await,
+                Some(Action::Restart) => return self.retry(wrapper).await,
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+    async fn pad_event(
+        &mut self,
+        _wrapper: &mut Wrapper<'_>,
+        _event: &PadEvent,
+        step: Option<MenuStep>,
+    ) -> Result<Option<Transition>> {
+        if let Some(step) = step {
+            self.menu.step(step);
+        }
+        Ok(None)
+    }
+    fn set_covered(&mut self, covered: bool) {
+        self.layer.set_is_active(!covered);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{
+    gamepad::{MenuStep, PadEvent},
+    Screen, Transition, Wrapper,
+};
+use quicksilver::{lifecycle::Event, Result};
+
+pub(crate) struct ScreenStack {
+    screens: Vec<Box<dyn Screen>>,
+}
+
+impl ScreenStack {
+    pub(crate) fn new(first: Box<dyn Screen>) -> Self {
+        Self {
+            screens: vec![first],
+        }
+    }
+    pub(crate) fn is_empty(&self) -> bool {
+        self.screens.is_empty()
+    }
+    pub(crate) fn apply(&mut self, transition: Transition) {
+        match transition {
+            Transition::Push(screen) => {
+                if let Some(top) = self.screens.last_mut() {
+                    top.set_covered(true);
+                }
+                self.screens.push(screen);
+            }
+            Transition::Pop => {
+                self.screens.pop();
+                if let Some(top) = self.screens.last_mut() {
+                    top.set_covered(false);
+                }
+            }
+            Transition::Replace(screen) => {
+                self.screens.pop();
+                self.screens.push(screen);
+            }
+            Transition::ClearTo(screen) => {
+                self.screens.clear();
+                self.screens.push(screen);
+            }
+        }
+    }
+    pub(crate) async fn event(&mut self, wrapper: &mut Wrapper<'_>, event: &Event) -> Result<()> {
+        if let Some(top) = self.screens.last_mut() {
+            if let Some(transition) = top.event(wrapper, event).await? {
+                self.apply(transition);
+            }
+        }
+        Ok(())
+    }
+    pub(crate) async fn pad_event(
+        &mut self,
+        wrapper: &mut Wrapper<'_>,
+        event: &PadEvent,
+        step: Option<MenuStep>,
+    ) -> Result<()> {
+        if let Some(top) = self.screens.last_mut() {
+            if let Some(transition) = top.pad_event(wrapper, event, step).await? {
+                self.apply(transition);
+            }
+        }
+        Ok(())
+    }
+    pub(crate) async fn update(&mut self, wrapper: &mut Wrapper<'_>) -> Result<()> {
+        if let Some(top) = self.screens.last_mut() {
+            if let Some(transition) = top.update(wrapper).await? {
+                self.apply(transition);
+            }
+        }
+        Ok(())
+    }
+    pub(crate) async fn draw(&mut self, wrapper: &mut Wrapper<'_>) -> Result<()> {
+        let lowest_visible = self
+            .screens
+            .iter()
+            .rposition(|screen| !screen.is_overlay())
+            .unwrap_or(0);
+        for screen in &mut self.screens[lowest_visible..] {
+            screen.draw(wrapper).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+use crate::{
+    gamepad::{MenuStep, PadEvent},
+    loading::loading_screen,
+    screens::{
+        add_button, game::Game, level_select::LevelSelect, menu_row, options::OptionsScreen,
+        MenuNavigation,
+    },
+    seeds,
+    timer::RunTimer,
+    Screen, Transition, Wrapper,
+};
+use mergui::{
+    channels::{BasicClickable, Clickable},
+    LayerId, Response,
+};
+use quicksilver::{
+    geom::{Rectangle, Transform, Vector},
+    graphics::Image,
+    Result,
+};
+
+use async_trait::async_trait;
+
+pub struct Title {
+    background: Image,
+    layer: LayerId,
+    new_game: Response<BasicClickable>,
+    continue_game: Option<(u32, Response<BasicClickable>)>,
+    level_select: Option<Response<BasicClickable>>,
+    options: Response<BasicClickable>,
+    quit: Response<BasicClickable>,
+    menu: MenuNavigation,
+}
+
+impl Title {
+    pub(crate) fn new(wrapper: &mut Wrapper<'_>) -> Self {
+        let layer = wrapper.context.add_layer();
+        let new_game = add_button(wrapper, &layer, "New Game", menu_row(0));
+        let (continue_game, level_select) = match wrapper.reached_level {
+            Some(level) => (
+                Some((
+                    level,
+                    add_button(
+                        wrapper,
+                        &layer,
+                        &format!("Continue ({})", level),
+                        menu_row(1),
+                    ),
+                )),
+                Some(add_button(wrapper, &layer, "Level Select", menu_row(2))),
+            ),
+            None => (None, None),
+        };
+        let options = add_button(wrapper, &layer, "Options", menu_row(3));
+        let quit = add_button(wrapper, &layer, "Quit", menu_row(4));
+        let rows: &[usize] = if wrapper.reached_level.is_some() {
+            &[0, 1, 2, 3, 4]
+        } else {
+            &[0, 3, 4]
+        };
+        Self {
+            background: loading_screen(&wrapper.gfx),
+            layer,
+            new_game,
+            continue_game,
+            level_select,
+            options,
+            quit,
+            menu: MenuNavigation::new(rows.iter().copied().map(menu_row).collect()),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Screen for Title {
+    async fn draw(&mut self, wrapper: &mut Wrapper<'_>) -> Result<()> {
+        wrapper.gfx.set_transform(Transform::IDENTITY);
+        wrapper
+            .gfx
+            .draw_image(&self.background, Rectangle::new((0, 0), (640, 640)));
+        self.menu.draw(wrapper);
+        wrapper.draw_text("Heaven and Hell", Vector::new(140, 130))
+    }
+    async fn update(&mut self, wrapper: &mut Wrapper<'_>) -> Result<Option<Transition>> {
+        if self.new_game.channel.has_clicked() || self.menu.pressed(menu_row(0)) {
+            wrapper.new_run(seeds::run_seed());
+            return Ok(Some(Transition::ClearTo(Game::load(wrapper, 1).await?)));
+        }
+        if let Some((level, button)) = &mut self.continue_game {
+            if button.channel.has_clicked() || self.menu.pressed(menu_row(1)) {
+                wrapper.run_timer = RunTimer::default();
+                return Ok(Some(Transition::ClearTo(
+                    Game::load(wrapper, *level).await?,
+                )));
+            }
+        }
+        if let Some(button) = &mut self.level_select {
+            if button.channel.has_clicked() || self.menu.pressed(menu_row(2)) {
+                return Ok(Some(Transition::Push(Box::new(LevelSelect::new(wrapper)))));
+            }
+        }
+        if self.options.channel.has_clicked() || self.menu.pressed(menu_row(3)) {
+            return Ok(Some(Transition::Push(Box::new(OptionsScreen::new(
+                wrapper,
+            )))));
+        }
+        if self.quit.channel.has_clicked() || self.menu.pressed(menu_row(4)) {
+            wrapper.should_quit = true;
+        }
+        Ok(None)
+    }
+    async fn pad_event(
+        &mut self,
+        _wrapper: &mut Wrapper<'_>,
+        _event: &PadEvent,
+        step: Option<MenuStep>,
+    ) -> Result<Option<Transition>> {
+        if let Some(step) = step {
+            self.menu.step(step);
+        }
+        Ok(None)
+    }
+    fn set_covered(&mut self, covered: bool) {
+        self.layer.set_is_active(!covered);
+    }
+}
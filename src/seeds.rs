@@ -0,0 +1,43 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+pub const MAZE: u64 = 1;
+pub const STARS: u64 = 2;
+pub const TILES: u64 = 3;
+pub const HAZARDS: u64 = 4;
+pub const HELL: u64 = 5;
+
+fn splitmix(mut value: u64) -> u64 {
+    value = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
+
+pub fn derive(seed: u64, salt: u64) -> u64 {
+    splitmix(seed ^ splitmix(salt))
+}
+
+/// Unlike `StdRng` the algorithm is fixed, so a seed keeps giving the same levels after updating rand.
+pub fn rng(seed: u64) -> ChaCha20Rng {
+    ChaCha20Rng::seed_from_u64(seed)
+}
+
+pub fn run_seed() -> u64 {
+    let mut args = std::env::args().skip_while(|arg| arg != "--seed").skip(1);
+    args.next()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or_else(rand::random)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn seeds_keep_their_values() {
+        assert_eq!(derive(0, MAZE), 6_791_897_765_849_424_158);
+        assert_eq!(rng(0).next_u64(), 449_479_075_714_955_186);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{
+    movement::{explore_from, find_start, touches_end, Cell, JumpEnvelope, Move},
+    Block,
+};
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Solution {
+    pub start: Cell,
+    pub moves: Vec<Move>,
+}
+
+impl Solution {
+    pub fn path(&self) -> Vec<Cell> {
+        let mut path = vec![self.start];
+        for step in &self.moves {
+            path.extend(step.path.iter().copied());
+        }
+        path.dedup();
+        path
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Unsolvable {
+    MissingStart,
+    MissingEnd,
+    EndUnreachable { reachable: usize },
+}
+
+impl Display for Unsolvable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Unsolvable::MissingStart => write!(f, "the level has no player start"),
+            Unsolvable::MissingEnd => write!(f, "the level has no end"),
+            Unsolvable::EndUnreachable { reachable } => write!(
+                f,
+                "the end can't be reached, the player can only get to {} places to stand in",
+                reachable
+            ),
+        }
+    }
+}
+
+impl Error for Unsolvable {}
+
+pub fn solve(level: &[Vec<Block>], envelope: JumpEnvelope) -> Result<Solution, Unsolvable> {
+    let start = find_start(level).ok_or(Unsolvable::MissingStart)?;
+    solve_from(level, start, envelope)
+}
+
+pub fn solve_from(
+    level: &[Vec<Block>],
+    start: Cell,
+    envelope: JumpEnvelope,
+) -> Result<Solution, Unsolvable> {
+    if !level
+        .iter()
+        .flatten()
+        .any(|block| *block == Block::PlayerEnd)
+    {
+        return Err(Unsolvable::MissingEnd);
+    }
+    let exploration = explore_from(level, start, envelope);
+    let (mut at, mut last_move) = exploration.end.ok_or(Unsolvable::EndUnreachable {
+        reachable: exploration.standing.len(),
+    })?;
+    //there is no need to keep falling once the end has been touched
+    if let Some(touching) = last_move
+        .path
+        .iter()
+        .position(|&cell| touches_end(level, cell))
+    {
+        last_move.path.truncate(touching + 1);
+    }
+    let mut moves = vec![last_move];
+    while let Some(Some((from, step))) = exploration.standing.get(&at) {
+        moves.push(step.clone());
+        at = *from;
+    }
+    moves.reverse();
+    Ok(Solution { start, moves })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{levels::parse_level, tuning::Tuning};
+
+    fn envelope() -> JumpEnvelope {
+        JumpEnvelope::from_physics(&Tuning::default(), 2, 32.)
+    }
+
+    #[test]
+    fn solves_first_level() {
+        let level = parse_level("1.txt", include_str!("../static/levels/1.txt")).unwrap();
+        let solution = solve(&level.heaven, envelope()).unwrap();
+        let path = solution.path();
+        assert_eq!(path.first(), find_start(&level.heaven).as_ref());
+        assert!(touches_end(&level.heaven, *path.last().unwrap()));
+    }
+
+    #[test]
+    fn wall_too_high_is_unsolvable() {
+        let level = parse_level(
+            "wall.txt",
+            "bbbbbbb\nbaaabab\nbaaabab\nbaaabab\nbaaabab\nbpaabeb\nbbbbbbb\n",
+        )
+        .unwrap();
+        assert_eq!(
+            solve(&level.heaven, envelope()),
+            Err(Unsolvable::EndUnreachable { reachable: 3 })
+        );
+    }
+
+    #[test]
+    fn missing_end_is_unsolvable() {
+        let level = vec![
+            vec![Block::Dirt; 3],
+            vec![Block::Dirt, Block::PlayerStart, Block::Dirt],
+            vec![Block::Dirt; 3],
+        ];
+        assert_eq!(solve(&level, envelope()), Err(Unsolvable::MissingEnd));
+    }
+}
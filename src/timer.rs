@@ -0,0 +1,32 @@
+use crate::timestep::UPDATES_PER_SECOND;
+
+pub fn format_time(updates: u32) -> String {
+    let hundredths = (f64::from(updates) * 100. / UPDATES_PER_SECOND).round() as u32;
+    format!(
+        "{}:{:02}.{:02}",
+        hundredths / 6000,
+        hundredths / 100 % 60,
+        hundredths % 100
+    )
+}
+
+pub fn format_difference(updates: u32, compared_to: u32) -> String {
+    if updates < compared_to {
+        format!("-{}", format_time(compared_to - updates))
+    } else {
+        format!("+{}", format_time(updates - compared_to))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Split {
+    pub level: u32,
+    pub updates: u32,
+    pub best_before: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RunTimer {
+    pub total: u32,
+    pub splits: Vec<Split>,
+}
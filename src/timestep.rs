@@ -0,0 +1,38 @@
+use instant::{Duration, Instant};
+
+pub const UPDATES_PER_SECOND: f64 = 60.;
+
+const MAX_UPDATES_PER_FRAME: u32 = 5;
+
+pub struct FixedTimestep {
+    step: Duration,
+    accumulated: Duration,
+    last: Instant,
+}
+
+impl FixedTimestep {
+    pub fn new(updates_per_second: f64) -> Self {
+        Self {
+            step: Duration::from_secs_f64(1. / updates_per_second),
+            accumulated: Duration::from_secs(0),
+            last: Instant::now(),
+        }
+    }
+    pub fn advance(&mut self) -> u32 {
+        let now = Instant::now();
+        self.accumulated += now - self.last;
+        self.last = now;
+        let mut updates = 0;
+        while self.accumulated >= self.step {
+            self.accumulated -= self.step;
+            updates += 1;
+        }
+        if updates > MAX_UPDATES_PER_FRAME {
+            updates = MAX_UPDATES_PER_FRAME;
+        }
+        updates
+    }
+    pub fn progress(&self) -> f32 {
+        (self.accumulated.as_secs_f64() / self.step.as_secs_f64()) as f32
+    }
+}
@@ -0,0 +1,252 @@
+use crate::seeds::derive;
+#[cfg(debug_assertions)]
+use instant::{Duration, Instant};
+use quicksilver::{load_file, log};
+use serde::Deserialize;
+
+const TUNING_FILE: &str = "movement.toml";
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Tuning {
+    pub gravity: f64,
+    pub jump_speed: f64,
+    pub top_speed: f64,
+    pub ground_acceleration: f64,
+    pub ground_deceleration: f64,
+    pub air_acceleration: f64,
+    pub air_deceleration: f64,
+    pub wall_slide_speed: f64,
+    pub wall_jump_speed: f64,
+    pub wall_jump_kick: f64,
+    pub width: f64,
+    pub height: f64,
+    pub density: f64,
+    pub coyote_updates: u32,
+    pub buffer_updates: u32,
+    pub cut_short: f64,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            gravity: 9.81 * 32.,
+            jump_speed: 200.,
+            top_speed: 160.,
+            ground_acceleration: 1200.,
+            ground_deceleration: 1600.,
+            air_acceleration: 700.,
+            air_deceleration: 250.,
+            wall_slide_speed: 60.,
+            wall_jump_speed: 180.,
+            wall_jump_kick: 150.,
+            width: 16.5,
+            height: 30.,
+            density: 2.,
+            coyote_updates: 6,
+            buffer_updates: 6,
+            cut_short: 0.45,
+        }
+    }
+}
+
+impl Tuning {
+    pub fn walk(&self, speed: f64, direction: f64, on_floor: bool, delta: f64) -> f64 {
+        let target = direction * self.top_speed;
+        let speeding_up =
+            direction != 0. && speed * direction >= 0. && speed.abs() < self.top_speed;
+        let rate = match (on_floor, speeding_up) {
+            (true, true) => self.ground_acceleration,
+            (true, false) => self.ground_deceleration,
+            (false, true) => self.air_acceleration,
+            (false, false) => self.air_deceleration,
+        };
+        let step = rate * delta;
+        if (target - speed).abs() <= step {
+            target
+        } else {
+            speed + step * (target - speed).signum()
+        }
+    }
+    //replays only play back the same way with the same movement, so they remember what it was
+    pub fn hash(&self) -> u64 {
+        [
+            self.gravity,
+            self.jump_speed,
+            self.top_speed,
+            self.ground_acceleration,
+            self.ground_deceleration,
+            self.air_acceleration,
+            self.air_deceleration,
+            self.wall_slide_speed,
+            self.wall_jump_speed,
+            self.wall_jump_kick,
+            self.width,
+            self.height,
+            self.density,
+            self.cut_short,
+        ]
+        .iter()
+        .map(|value| value.to_bits())
+        .chain(vec![
+            u64::from(self.coyote_updates),
+            u64::from(self.buffer_updates),
+        ])
+        .fold(0, derive)
+    }
+    pub async fn load() -> Self {
+        match load_file(TUNING_FILE).await {
+            Ok(loaded) => Self::parse(&loaded).unwrap_or_else(|err| {
+                log::warn!("{} could not be parsed: {}", TUNING_FILE, err);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+    fn parse(loaded: &[u8]) -> Result<Self, toml::de::Error> {
+        toml::from_slice(loaded)
+    }
+}
+
+#[cfg(debug_assertions)]
+pub struct TuningReloader {
+    last: Instant,
+    contents: Option<Vec<u8>>,
+}
+
+#[cfg(debug_assertions)]
+impl TuningReloader {
+    pub fn new() -> Self {
+        Self {
+            last: Instant::now(),
+            contents: None,
+        }
+    }
+    pub async fn reload(&mut self, tuning: &mut Tuning) {
+        if self.last.elapsed() < Duration::from_secs(1) {
+            return;
+        }
+        self.last = Instant::now();
+        let loaded = match load_file(TUNING_FILE).await {
+            Ok(loaded) => loaded,
+            Err(_) => return,
+        };
+        if self.contents.as_ref() == Some(&loaded) {
+            return;
+        }
+        //a broken file is most likely being edited, so the values in use are kept until it is fixed
+        match Tuning::parse(&loaded) {
+            Ok(reloaded) if reloaded != *tuning => {
+                log::info!("Reloaded {}", TUNING_FILE);
+                *tuning = reloaded;
+            }
+            Ok(_) => {}
+            Err(err) => log::warn!("{} could not be parsed: {}", TUNING_FILE, err),
+        }
+        self.contents = Some(loaded);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_file_matches_defaults() {
+        let shipped = Tuning::parse(include_bytes!("../static/movement.toml")).unwrap();
+        assert_eq!(shipped, Tuning::default());
+    }
+
+    #[test]
+    fn hash_changes_with_any_value() {
+        let tuning = Tuning::default();
+        assert_eq!(tuning.hash(), Tuning::default().hash());
+        let changed = [
+            Tuning {
+                cut_short: 0.5,
+                ..tuning
+            },
+            Tuning {
+                buffer_updates: 7,
+                ..tuning
+            },
+            Tuning {
+                gravity: tuning.gravity + 1.,
+                ..tuning
+            },
+        ];
+        for other in &changed {
+            assert_ne!(other.hash(), tuning.hash(), "{:?}", other);
+        }
+    }
+
+    #[test]
+    fn left_out_values_keep_defaults() {
+        let tuning = Tuning::parse(b"top_speed = 100.0").unwrap();
+        assert_eq!(
+            tuning,
+            Tuning {
+                top_speed: 100.,
+                ..Tuning::default()
+            }
+        );
+        assert!(Tuning::parse(b"top_sped = 100.0").is_err());
+    }
+
+    const DELTA: f64 = 1. / 60.;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn walking_speeds_up_by_the_acceleration() {
+        let tuning = Tuning::default();
+        assert!(close(
+            tuning.walk(0., 1., true, DELTA),
+            tuning.ground_acceleration * DELTA
+        ));
+        assert!(close(
+            tuning.walk(0., -1., false, DELTA),
+            -tuning.air_acceleration * DELTA
+        ));
+    }
+
+    #[test]
+    fn walking_stops_at_top_speed() {
+        let tuning = Tuning::default();
+        let almost = tuning.top_speed - 1.;
+        assert!(close(
+            tuning.walk(almost, 1., true, DELTA),
+            tuning.top_speed
+        ));
+        assert!(close(
+            tuning.walk(tuning.top_speed, 1., false, DELTA),
+            tuning.top_speed
+        ));
+    }
+
+    #[test]
+    fn speeds_above_top_speed_slow_down() {
+        let tuning = Tuning::default();
+        let fast = tuning.top_speed * 2.;
+        assert!(close(
+            tuning.walk(fast, 1., false, DELTA),
+            fast - tuning.air_deceleration * DELTA
+        ));
+    }
+
+    #[test]
+    fn turning_around_and_stopping_use_the_deceleration() {
+        let tuning = Tuning::default();
+        assert!(close(
+            tuning.walk(100., -1., true, DELTA),
+            100. - tuning.ground_deceleration * DELTA
+        ));
+        assert!(close(tuning.walk(10., 0., true, DELTA), 0.));
+        assert!(close(
+            tuning.walk(-100., 0., false, DELTA),
+            -100. + tuning.air_deceleration * DELTA
+        ));
+    }
+}